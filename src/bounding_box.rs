@@ -0,0 +1,76 @@
+use std::ops::{Add, Mul, Sub};
+
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{r2::R2, shape::Shape, sqrt::Sqrt, trig::Trig, dual::Dual};
+
+#[derive(Debug, Clone, PartialEq, Tsify, Serialize, Deserialize)]
+pub struct BoundingBox<D> {
+    pub min: R2<D>,
+    pub max: R2<D>,
+}
+
+pub trait BoundingBoxArg
+: Clone
++ Add<Output = Self>
++ Sub<Output = Self>
++ Mul<Output = Self>
++ Sqrt
++ Trig
+{}
+impl BoundingBoxArg for f64 {}
+impl BoundingBoxArg for Dual {}
+
+impl<D: BoundingBoxArg> Shape<D> {
+    /// Axis-aligned bounding box; for rotated ellipses, the half-extents are given by the ellipse's support function along each axis.
+    pub fn bounding_box(&self) -> BoundingBox<D> {
+        let (c, r) = match self {
+            Shape::Circle(c) => (c.c.clone(), R2 { x: c.r.clone(), y: c.r.clone() }),
+            Shape::XYRR(e) => (e.c.clone(), e.r.clone()),
+            Shape::XYRRT(e) => {
+                let cos = e.t.cos();
+                let sin = e.t.sin();
+                let rx_cos = e.r.x.clone() * cos.clone();
+                let rx_sin = e.r.x.clone() * sin.clone();
+                let ry_cos = e.r.y.clone() * cos;
+                let ry_sin = e.r.y.clone() * sin;
+                let x = (rx_cos.clone() * rx_cos + ry_sin.clone() * ry_sin).sqrt();
+                let y = (rx_sin.clone() * rx_sin + ry_cos.clone() * ry_cos).sqrt();
+                (e.c.clone(), R2 { x, y })
+            },
+        };
+        BoundingBox {
+            min: R2 { x: c.x.clone() - r.x.clone(), y: c.y.clone() - r.y.clone() },
+            max: R2 { x: c.x + r.x, y: c.y + r.y },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::shape::{circle, xyrr, xyrrt};
+
+    use super::*;
+    use test_log::test;
+
+    fn check(shape: Shape<f64>, min: (f64, f64), max: (f64, f64)) {
+        let BoundingBox { min: actual_min, max: actual_max } = shape.bounding_box();
+        assert_relative_eq!(actual_min.x, min.0, epsilon = 1e-12);
+        assert_relative_eq!(actual_min.y, min.1, epsilon = 1e-12);
+        assert_relative_eq!(actual_max.x, max.0, epsilon = 1e-12);
+        assert_relative_eq!(actual_max.y, max.1, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn shapes() {
+        check(circle(1., 2., 3.), (-2., -1.), (4., 5.));
+        check(xyrr(1., 2., 3., 4.), (-2., -2.), (4., 6.));
+        check(xyrrt(1., 2., 3., 4., 0.), (-2., -2.), (4., 6.));
+        check(xyrrt(1., 2., 3., 4., PI / 2.), (-3., -1.), (5., 5.));
+        let h = (12.5f64).sqrt();
+        check(xyrrt(0., 0., 3., 4., PI / 4.), (-h, -h), (h, h));
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::step::Step;
+
+/// Lower bound applied to radius coordinates (`r`, `rx`, `ry`) that don't have an explicit minimum.
+pub const DEFAULT_MIN_RADIUS: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Tsify, Serialize, Deserialize)]
+pub struct Bound {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// Per-coordinate box constraints, keyed by coordinate name. Keys can be shape-specific, using the same `"<shape idx>.<coord>"` names as [`crate::coord_getter::CoordGetters`] (e.g. `"1.r"`), or bare coordinate names (e.g. `"r"`) that apply to every shape; shape-specific keys take precedence.
+#[derive(Debug, Clone, Default, derive_more::Deref, Tsify, Serialize, Deserialize)]
+pub struct Bounds(pub BTreeMap<String, Bound>);
+
+impl Bounds {
+    pub fn get(&self, shape_idx: usize, name: &str) -> Bound {
+        let bound = self.0.get(&format!("{}.{}", shape_idx, name)).or_else(|| self.0.get(name)).cloned().unwrap_or_default();
+        if bound.min.is_none() && (name == "r" || name == "rx" || name == "ry") {
+            Bound { min: Some(DEFAULT_MIN_RADIUS), ..bound }
+        } else {
+            bound
+        }
+    }
+
+    /// Shrink elements of `step_vec` (a candidate update to `step`'s trainable coordinates) so that each updated coordinate lies within its bounds.
+    pub fn project(&self, step: &Step, step_vec: &mut [f64]) {
+        for (shape_idx, shape) in step.shapes.iter().enumerate() {
+            let v = shape.v();
+            for ((name, val), dual) in v.names().iter().zip(v.vals()).zip(shape.duals()) {
+                let Bound { min, max } = self.get(shape_idx, name);
+                for (delta, d) in step_vec.iter_mut().zip(dual) {
+                    if d == 0. {
+                        continue;
+                    }
+                    let mut nxt = val + *delta;
+                    if let Some(min) = min {
+                        nxt = nxt.max(min);
+                    }
+                    if let Some(max) = max {
+                        nxt = nxt.min(max);
+                    }
+                    *delta = nxt - val;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{duals::{D, Z}, shape::circle, targets::Targets};
+
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn project() {
+        let step = Step::new(
+            vec![
+                (circle(0., 0., 1.), vec![ Z, Z, Z, ]),
+                (circle(1., 0., 1.), vec![ D, Z, D, ]),
+            ],
+            Targets::new([ ("0*", 3.), ("*1", 1.), ("01", 0.5) ].into_iter().map(|(k, v)| (k.to_string(), v)).collect()),
+        );
+        let mut bounds = Bounds::default();
+        bounds.0.insert("1.cx".to_string(), Bound { min: None, max: Some(1.5) });
+
+        // Step vector is over the two trainable coordinates, "1.cx" and "1.r"
+        let mut step_vec = vec![ 1., -2. ];
+        bounds.project(&step, &mut step_vec);
+        assert_eq!(step_vec, vec![ 0.5, DEFAULT_MIN_RADIUS - 1. ]);
+
+        let mut step_vec = vec![ 0.25, 0.5 ];
+        bounds.project(&step, &mut step_vec);
+        assert_eq!(step_vec, vec![ 0.25, 0.5 ]);
+    }
+}
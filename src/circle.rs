@@ -15,7 +15,7 @@ use crate::{
     shape::{AreaArg, Duals, Shape, Shapes},
     sqrt::Sqrt,
     to::To,
-    transform::Transform::{self, Rotate, Scale, ScaleXY, Translate},
+    transform::Transform::{self, Reflect, Rotate, Scale, ScaleXY, Translate},
     transform::{CanTransform, Projection},
 };
 use derive_more::From;
@@ -24,6 +24,7 @@ use serde::{Deserialize, Serialize};
 use tsify::Tsify;
 
 #[derive(Debug, Clone, Copy, From, PartialEq, Tsify, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Circle<D> {
     pub c: R2<D>,
     pub r: D,
@@ -62,6 +63,7 @@ impl Circle<f64> {
         }
     }
     pub fn names(&self) -> [String; 3] { Self::getters().map(|g| g.name).into() }
+    pub fn perimeter(&self) -> f64 { 2. * PI * self.r }
     pub fn vals(&self) -> [f64; 3] { [ self.c.x, self.c.y, self.r ] }
 }
 
@@ -242,6 +244,11 @@ where
                 let r = self.r.clone();
                 Circle { c, r }.into()
             }
+            Reflect(v) => {
+                let c = self.c.reflect(v);
+                let r = self.r.clone();
+                Circle { c, r }.into()
+            }
         }
     }
 }
@@ -312,6 +319,40 @@ impl Add<R2<i64>> for Circle<f64> {
     }
 }
 
+/// Closed-form ("radical line") intersections of two circles: the points lie on the line through `self.c + a·d` perpendicular to `d = other.c - self.c`, at distance `k·|d|` on either side. Much cheaper than the general (quartic) path, and used for circle pairs by [`Shape::intersections`].
+///
+/// Returns no points for concentric or non-overlapping circles; tangent circles yield a doubled point (with NaN gradients, as in [`Circle::unit_intersections`]).
+impl<D: UnitIntersectionsArg> Intersect<Circle<D>, D> for Circle<D> {
+    fn intersect(&self, other: &Circle<D>) -> Vec<R2<D>> {
+        let R2 { x: x0, y: y0 } = self.c.clone();
+        let dx = other.c.x.clone() - x0.clone();
+        let dy = other.c.y.clone() - y0.clone();
+        let d2 = dx.clone() * dx.clone() + dy.clone() * dy.clone();
+        if d2.clone().into() == 0. {
+            return vec![];
+        }
+        let r0sq = self.r.clone() * self.r.clone();
+        let r1sq = other.r.clone() * other.r.clone();
+        // Fraction of the way from `self.c` to `other.c` where the radical line crosses
+        let a = (r0sq.clone() - r1sq + d2.clone()) / (d2.clone() * 2.);
+        // Half-chord length, as a fraction of `|d|`
+        let k2 = r0sq / d2 - a.clone() * a.clone();
+        if k2.clone().into() < 0. {
+            return vec![];
+        }
+        let k = k2.sqrt();
+        let x = x0 + a.clone() * dx.clone();
+        let y = y0 + a * dy.clone();
+        // Same order as the quartic path (via [`Circle::unit_intersections`]): greater `y` first, or greater `x` if the centers are vertically aligned
+        let swap = if dx.clone().into() == 0. { dy.clone().into() > 0. } else { dx.clone().into() < 0. };
+        let p0 = R2 { x: x.clone() - k.clone() * dy.clone(), y: y.clone() + k.clone() * dx.clone() };
+        let p1 = R2 { x: x + k.clone() * dy, y: y - k * dx };
+        let intersections = if swap { vec![ p1, p0 ] } else { vec![ p0, p1 ] };
+        debug!("Circle::intersect: {} ∩ {}: {:?}", self, other, intersections);
+        intersections
+    }
+}
+
 impl Intersect<Circle<f64>, D> for Circle<f64> {
     fn intersect(&self, other: &Circle<f64>) -> Vec<R2<D>> {
         let [ s0, s1 ] = Shapes::from([
@@ -331,6 +372,15 @@ impl<D: UnitCircleGap> Circle<D> {
             None
         }
     }
+    /// How far this circle extends into the unit circle (`1 + r - |c|`), if they overlap.
+    pub fn unit_circle_overlap(&self) -> Option<D> {
+        let depth = self.r.clone() - (self.c.norm() - 1.);
+        if depth.clone().into() > 0. {
+            Some(depth)
+        } else {
+            None
+        }
+    }
 }
 
 #[cfg(test)]
@@ -449,4 +499,70 @@ mod tests {
             r2( 1., vec![ 1., 0., 1., 0., 0.,  0. ], 0., vec![ 0., 0., 0., 0., 1., -1. ]),
         );
     }
+
+    fn closed_form_and_quartic(c0: Circle<f64>, c1: Circle<f64>) -> (Vec<R2<D>>, Vec<R2<D>>) {
+        let [ s0, s1 ] = Shapes::from([
+            (Shape::Circle(c0), vec![ true; 3 ]),
+            (Shape::Circle(c1), vec![ true; 3 ]),
+        ]);
+        let (Shape::Circle(d0), Shape::Circle(d1)) = (&s0, &s1) else { unreachable!() };
+        (d0.intersect(d1), s0._intersect(&s1))
+    }
+
+    #[test]
+    fn closed_form_matches_quartic() {
+        let circles = [
+            (Circle { c: R2 { x: 0., y: 0. }, r: 1. }, Circle { c: R2 { x: 1., y: 0. }, r: 1. }),
+            (Circle { c: R2 { x: 0., y: 0. }, r: 1. }, Circle { c: R2 { x: 0., y: 1. }, r: 1. }),
+            (Circle { c: R2 { x: 0., y: 0. }, r: 1. }, Circle { c: R2 { x: 0., y: -1. }, r: 1. }),
+            (Circle { c: R2 { x: 0., y: 0. }, r: 1. }, Circle { c: R2 { x: -1., y: 0.3 }, r: 0.5 }),
+            (Circle { c: R2 { x: 0.3, y: -0.2 }, r: 1.3 }, Circle { c: R2 { x: 1.1, y: 0.9 }, r: 0.7 }),
+            (Circle { c: R2 { x: -2., y: 1. }, r: 2.5 }, Circle { c: R2 { x: 1., y: 3. }, r: 1.5 }),
+            (Circle { c: R2 { x: 1., y: 1. }, r: 0.2 }, Circle { c: R2 { x: 1.1, y: 1.05 }, r: 0.25 }),
+        ];
+        for (c0, c1) in circles {
+            for (c0, c1) in [ (c0, c1), (c1, c0) ] {
+                let (closed, quartic) = closed_form_and_quartic(c0, c1);
+                assert_eq!(closed.len(), 2, "{} ∩ {}", c0, c1);
+                assert_eq!(quartic.len(), 2, "{} ∩ {}", c0, c1);
+                for (p, q) in closed.iter().zip(quartic.iter()) {
+                    assert_relative_eq!(p.x.v(), q.x.v(), epsilon = 1e-12);
+                    assert_relative_eq!(p.y.v(), q.y.v(), epsilon = 1e-12);
+                    for (dp, dq) in p.x.d().iter().zip(q.x.d().iter()).chain(p.y.d().iter().zip(q.y.d().iter())) {
+                        assert_relative_eq!(dp, dq, epsilon = 1e-12);
+                    }
+                }
+            }
+        }
+        // No intersections: disjoint, nested, concentric
+        for (c0, c1) in [
+            (Circle { c: R2 { x: 0., y: 0. }, r: 1. }, Circle { c: R2 { x: 3., y: 0. }, r: 1. }),
+            (Circle { c: R2 { x: 0., y: 0. }, r: 2. }, Circle { c: R2 { x: 0.5, y: 0. }, r: 1. }),
+            (Circle { c: R2 { x: 0., y: 0. }, r: 2. }, Circle { c: R2 { x: 0., y: 0. }, r: 1. }),
+        ] {
+            let (closed, _) = closed_form_and_quartic(c0, c1);
+            assert_eq!(closed, vec![], "{} ∩ {}", c0, c1);
+            assert_eq!(Shape::Circle(c0).intersection_points(&Shape::Circle(c1)), vec![]);
+        }
+    }
+
+    /// `cargo test --release -- --ignored closed_form_bench --nocapture`
+    #[test]
+    #[ignore]
+    fn closed_form_bench() {
+        use std::time::Instant;
+        let [ s0, s1 ] = Shapes::from([
+            (Shape::Circle(Circle { c: R2 { x: 0.3, y: -0.2 }, r: 1.3 }), vec![ true; 3 ]),
+            (Shape::Circle(Circle { c: R2 { x: 1.1, y: 0.9 }, r: 0.7 }), vec![ true; 3 ]),
+        ]);
+        let (Shape::Circle(c0), Shape::Circle(c1)) = (&s0, &s1) else { unreachable!() };
+        let reps = 100_000;
+        let start = Instant::now();
+        for _ in 0..reps { s0._intersect(&s1); }
+        let quartic = start.elapsed();
+        let start = Instant::now();
+        for _ in 0..reps { c0.intersect(c1); }
+        let closed = start.elapsed();
+        println!("quartic {:?}, closed form {:?} (per call)", quartic / reps, closed / reps);
+    }
 }
@@ -5,7 +5,7 @@ use log::{debug, error};
 use serde::{Serialize, Deserialize};
 use tsify::Tsify;
 
-use crate::{node::{N, Node}, math::deg::Deg, edge::{E, self, EdgeArg, Edge}, contains::{Contains, ShapeContainsPoint}, region::{Region, RegionArg}, segment::Segment, set::S, theta_points::{ThetaPoints, ThetaPointsArg}, r2::R2, to::To, zero::Zero, fmt::Fmt, hull::Hull, shape::AreaArg};
+use crate::{node::{N, Node}, math::{deg::Deg, kahan::KahanSum}, edge::{E, self, EdgeArg, Edge}, contains::{Contains, ShapeContainsPoint}, region::{Region, RegionArg}, segment::Segment, set::S, theta_points::{ThetaPoints, ThetaPointsArg}, r2::R2, to::To, zero::Zero, fmt::Fmt, hull::Hull, shape::AreaArg};
 
 pub type C<D> = Rc<RefCell<Component<D>>>;
 
@@ -418,7 +418,7 @@ impl<D: RegionArg> Component<D>
 where R2<D>: To<R2<f64>>,
 {
     pub fn area(&self) -> D {
-        self.regions.iter().map(|r| r.total_area.clone()).sum::<D>()
+        self.regions.iter().map(|r| r.total_area.clone()).kahan_sum()
     }
 }
 
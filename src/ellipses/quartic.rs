@@ -8,6 +8,41 @@ use crate::{dual::{D, Dual}, fmt::Fmt, zero::Zero, math::quartic::quartic};
 #[derive(Debug, Clone)]
 pub struct Root<D>(pub D, pub bool);  // (root, is_double_root?)
 
+/// Max Newton-Raphson iterations used to polish each real root returned by the closed-form solver.
+pub const NEWTON_POLISH_ITERS: usize = 4;
+
+/// Roots whose residual, relative to the magnitude of the polynomial's terms at that root, is below this are considered exact, and left as-is by [`Quartic::quartic_roots`].
+pub const NEWTON_POLISH_REL_TOL: f64 = 1e-12;
+
+/// Evaluate `a_4 x⁴ + a_3 x³ + a_2 x² + a_1 x + a_0`, returning the value and the sum of the absolute values of its terms (used to measure residuals relative to the scale of the polynomial at `x`).
+fn eval_quartic(a_4: f64, a_3: f64, a_2: f64, a_1: f64, a_0: f64, x: f64) -> (f64, f64) {
+    let f = (((a_4 * x + a_3) * x + a_2) * x + a_1) * x + a_0;
+    let ax = x.abs();
+    let scale = (((a_4.abs() * ax + a_3.abs()) * ax + a_2.abs()) * ax + a_1.abs()) * ax + a_0.abs();
+    (f, scale)
+}
+
+/// Refine an approximate root `x` of `a_4 x⁴ + a_3 x³ + a_2 x² + a_1 x + a_0` with up to [`NEWTON_POLISH_ITERS`] Newton-Raphson iterations, stopping early once an iteration fails to reduce the residual (e.g. near a multiple root, where the derivative vanishes).
+pub fn polish_root(a_4: f64, a_3: f64, a_2: f64, a_1: f64, a_0: f64, x: f64) -> f64 {
+    let fp = |x: f64| ((4. * a_4 * x + 3. * a_3) * x + 2. * a_2) * x + a_1;
+    let mut x = x;
+    let mut fx = eval_quartic(a_4, a_3, a_2, a_1, a_0, x).0;
+    for _ in 0..NEWTON_POLISH_ITERS {
+        let d = fp(x);
+        if fx == 0. || d == 0. || !d.is_finite() {
+            break;
+        }
+        let nx = x - fx / d;
+        let nfx = eval_quartic(a_4, a_3, a_2, a_1, a_0, nx).0;
+        if nfx.is_nan() || nfx.abs() >= fx.abs() {
+            break;
+        }
+        x = nx;
+        fx = nfx;
+    }
+    x
+}
+
 pub trait Quartic
 where
     Self: Sized
@@ -24,7 +59,14 @@ impl Quartic for f64 {
         debug!("{}x^4 + {}x^3 + {}x^2 + {}x + {}", a_4, a_3, a_2, a_1, a_0);
         let roots0 = quartic(a_4, a_3, a_2, a_1, a_0);
         // debug!("roots0: {:?}", roots0);
-        let reals = roots0.reals();
+        let reals: Vec<f64> = roots0.reals().into_iter().map(|x| {
+            let (fx, scale) = eval_quartic(a_4, a_3, a_2, a_1, a_0, x);
+            if fx.abs() > NEWTON_POLISH_REL_TOL * scale {
+                polish_root(a_4, a_3, a_2, a_1, a_0, x)
+            } else {
+                x
+            }
+        }).collect();
 
         // roots::{find_roots_stur, find_roots_eigen}
         // let mut first_nonzero: Option<f64> = None;
@@ -134,3 +176,23 @@ impl Quartic for Dual {
         dual_roots
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use test_log::test;
+
+    #[test]
+    fn polish_irrational_roots() {
+        // x⁴ - 5x² + 6 = (x² - 2)(x² - 3): roots ±√2, ±√3
+        let f = |x: f64| x.powi(4) - 5. * x.powi(2) + 6.;
+        let expected = [ -(3_f64.sqrt()), -(2_f64.sqrt()), 2_f64.sqrt(), 3_f64.sqrt() ];
+        for (&root, perturbation) in expected.iter().cartesian_product([ -1e-6, -1e-8, 1e-8, 1e-6 ]) {
+            // Start from a perturbed estimate, like the closed-form solver returns near grazing intersections
+            let polished = polish_root(1., 0., -5., 0., 6., root + perturbation);
+            assert!(f(polished).abs() < 1e-13, "root {}: residual {}", polished, f(polished));
+            assert!((polished - root).abs() < 1e-14, "{} vs {}", polished, root);
+        }
+    }
+}
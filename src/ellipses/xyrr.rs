@@ -6,11 +6,12 @@ use log::{debug, warn};
 use serde::{Deserialize, Serialize};
 use tsify::Tsify;
 
-use crate::{r2::R2, rotate::{Rotate as _Rotate, RotateArg}, dual::{D, Dual}, shape::{Duals, Shape, AreaArg}, transform::{Transform::{Rotate, Scale, ScaleXY, Translate, self}, CanProject, CanTransform, Projection}, math::{recip::Recip, deg::Deg, is_zero::IsZero}, sqrt::Sqrt, ellipses::xyrr, zero::Zero, coord_getter::{CoordGetter, coord_getter}};
+use crate::{r2::R2, rotate::{Rotate as _Rotate, RotateArg}, dual::{D, Dual}, shape::{Duals, Shape, AreaArg}, transform::{Transform::{Reflect, Rotate, Scale, ScaleXY, Translate, self}, CanProject, CanTransform, Projection}, math::{recip::Recip, deg::Deg, is_zero::IsZero}, sqrt::Sqrt, ellipses::xyrr, zero::Zero, coord_getter::{CoordGetter, coord_getter}};
 
 use super::{xyrrt::XYRRT, cdef::{CDEF, self}, bcdef};
 
 #[derive(Debug, Clone, From, PartialEq, Serialize, Deserialize, Tsify)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct XYRR<D> {
     pub c: R2<D>,
     pub r: R2<D>,
@@ -49,6 +50,12 @@ impl XYRR<f64> {
         }
     }
     pub fn names(&self) -> [String; 4] { Self::getters().map(|g| g.name).into() }
+    /// Ramanujan's second approximation, `π(a+b)(1 + 3h/(10 + √(4-3h)))` with `h = ((a-b)/(a+b))²`: exact for circles, and within ~0.04% even for degenerate (`b → 0`) ellipses.
+    pub fn perimeter(&self) -> f64 {
+        let (a, b) = (self.r.x, self.r.y);
+        let h = ((a - b) / (a + b)).powi(2);
+        PI * (a + b) * (1. + 3. * h / (10. + (4. - 3. * h).sqrt()))
+    }
     pub fn vals(&self) -> [f64; 4] { [ self.c.x, self.c.y, self.r.x, self.r.y ] }
 }
 
@@ -219,6 +226,7 @@ where R2<D>: TransformR2<D>,
             }),
             ScaleXY(xy) => Shape::XYRR(self.scale_xy(xy)),
             Rotate(a) => Shape::XYRRT(self.rotate(&a)),
+            Reflect(v) => Shape::XYRRT(XYRR { c: self.c.flip_y(), r: self.r.clone() }.rotate(&v.reflection_angle())),
         };
         rv
     }
@@ -258,6 +266,16 @@ impl<D: UnitCircleGap> XYRR<D> {
             None
         }
     }
+    /// Upper bound on how far this ellipse extends into the unit circle (`1 + max(rx, ry) - |c|`); positive whenever they overlap, since every boundary point is within `max(rx, ry)` of the center.
+    pub fn unit_circle_overlap(&self) -> Option<D> {
+        let r = &self.r;
+        let depth = (if r.x > r.y { r.x.clone() } else { r.y.clone() }) - (self.c.norm() - 1.);
+        if depth.clone().into() > 0. {
+            Some(depth)
+        } else {
+            None
+        }
+    }
 }
 
 impl<D: AbsDiffEq<Epsilon = f64> + Clone> AbsDiffEq for XYRR<D> {
@@ -496,4 +514,23 @@ mod tests {
             R2 { x: 0.5368069715813831, y: -0.8437050960862849 },
         ]);
     }
+
+    #[test]
+    fn perimeter() {
+        use crate::shape::{circle, xyrrt};
+        use std::f64::consts::TAU;
+        assert_relative_eq!(circle(0., 0., 1.).perimeter(), TAU, epsilon = 1e-15);
+        assert_relative_eq!(circle(3., -2., 2.5).perimeter(), 2.5 * TAU, epsilon = 1e-14);
+        // Ellipses with equal radii are circles
+        assert_relative_eq!(xyrr(1., 2., 1.5, 1.5).perimeter(), 1.5 * TAU, epsilon = 1e-14);
+        assert_relative_eq!(xyrrt(1., 2., 1.5, 1.5, 0.7).perimeter(), 1.5 * TAU, epsilon = 1e-14);
+        // Complete elliptic integral of the second kind: 4·a·E(1 - b²/a²)
+        let expected = 9.688448220547675;
+        assert_relative_eq!(xyrr(0., 0., 2., 1.).perimeter(), expected, max_relative = 1e-9);
+        assert_relative_eq!(xyrr(0., 0., 1., 2.).perimeter(), expected, max_relative = 1e-9);
+        // Rotation doesn't change arc length
+        assert_eq!(xyrrt(0., 0., 2., 1., 1.).perimeter(), xyrr(0., 0., 2., 1.).perimeter());
+        // Degenerate ellipse: a segment of length 2·rx, traversed twice
+        assert_relative_eq!(xyrr(0., 0., 1., 0.).perimeter(), 4., max_relative = 5e-4);
+    }
 }
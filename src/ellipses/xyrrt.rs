@@ -6,11 +6,12 @@ use log::debug;
 use serde::{Serialize, Deserialize};
 use tsify::Tsify;
 
-use crate::{r2::R2, rotate::{Rotate as _Rotate, RotateArg}, dual::{D, Dual}, shape::{Duals, Shape, AreaArg}, transform::{Transform::{Rotate, Scale, ScaleXY, Translate, self}, Projection, CanTransform, CanProject}, math::{recip::Recip, deg::Deg}, coord_getter::{CoordGetter, coord_getter}};
+use crate::{r2::R2, rotate::{Rotate as _Rotate, RotateArg}, dual::{D, Dual}, shape::{Duals, Shape, AreaArg}, transform::{Transform::{Reflect, Rotate, Scale, ScaleXY, Translate, self}, Projection, CanTransform, CanProject}, math::{recip::Recip, deg::Deg}, coord_getter::{CoordGetter, coord_getter}};
 
 use super::{xyrr::{XYRR, TransformD, TransformR2, UnitCircleGap, CdefArg}, cdef, bcdef::{BCDEF, self}};
 
 #[derive(Debug, Clone, From, PartialEq, Serialize, Deserialize, Tsify)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct XYRRT<D> {
     pub c: R2<D>,
     pub r: R2<D>,
@@ -39,6 +40,8 @@ impl XYRRT<f64> {
         self.bcdef().at_y(y)
     }
     pub fn names(&self) -> [String; 5] { Self::getters().map(|g| g.name).into() }
+    /// Same as the unrotated ellipse's (see [`XYRR::perimeter`]); rotation preserves arc length.
+    pub fn perimeter(&self) -> f64 { XYRR { c: self.c, r: self.r }.perimeter() }
     pub fn vals(&self) -> [f64; 5] { [ self.c.x, self.c.y, self.r.x, self.r.y, self.t ] }
 }
 
@@ -159,6 +162,7 @@ where R2<D>: TransformR2<D>,
                 // Shape::XYRRT(rotated)
             },
             Rotate(a) => Shape::XYRRT(self.rotate(&a)),
+            Reflect(v) => Shape::XYRRT(XYRRT { c: self.c.flip_y(), r: self.r.clone(), t: -self.t.clone() }.rotate(&v.reflection_angle())),
         };
         rv
     }
@@ -168,6 +172,9 @@ impl<D: LevelArg + UnitCircleGap> XYRRT<D> {
     pub fn unit_circle_gap(&self) -> Option<D> {
         self.level().unit_circle_gap()
     }
+    pub fn unit_circle_overlap(&self) -> Option<D> {
+        self.level().unit_circle_overlap()
+    }
 }
 
 impl<D: AbsDiffEq<Epsilon = f64> + Clone> AbsDiffEq for XYRRT<D> {
@@ -217,6 +224,33 @@ mod tests {
         assert_relative_eq!(l.r.y, 3.);
     }
 
+    #[test]
+    fn reflect() {
+        let e = XYRRT {
+            c: R2 { x: 1., y: 2. },
+            r: R2 { x: 2., y: 3. },
+            t: PI / 6.,
+        };
+        // Across the x-axis
+        let Shape::XYRRT(x) = e.transform(&Reflect(R2 { x: 1., y: 0. })) else { panic!() };
+        assert_relative_eq!(x, XYRRT { c: R2 { x: 1., y: -2. }, r: e.r, t: -PI / 6. }, epsilon = 1e-12);
+
+        let axis = R2 { x: 1., y: 2. };
+        let Shape::XYRRT(once) = e.transform(&Reflect(axis)) else { panic!() };
+        // Points on the boundary map to points on the reflected boundary
+        for theta in [ 0., 1., 2., 3., 4., 5. ] {
+            let p = R2 { x: e.r.x * f64::cos(theta), y: e.r.y * f64::sin(theta) }.rotate(&e.t) + e.c;
+            let q = p.reflect(&axis);
+            let u = (q - once.c).rotate(&-once.t);
+            assert_relative_eq!((u.x / once.r.x).powi(2) + (u.y / once.r.y).powi(2), 1., epsilon = 1e-12);
+        }
+        let Shape::XYRRT(twice) = once.transform(&Reflect(axis)) else { panic!() };
+        assert_relative_eq!(twice.c, e.c, epsilon = 1e-12);
+        assert_relative_eq!(twice.r, e.r, epsilon = 1e-12);
+        // Orientation angle is only defined mod π
+        assert_relative_eq!(((twice.t - e.t) / PI).round() * PI, twice.t - e.t, epsilon = 1e-12);
+    }
+
     #[test]
     fn unit_intersections1() {
         let e = XYRRT {
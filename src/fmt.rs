@@ -1,5 +1,34 @@
 use crate::dual::Dual;
 
+/// Magnitudes outside `[CANONICAL_MIN_DECIMAL, CANONICAL_MAX_DECIMAL)` are rendered in scientific notation by [`canonical_f64`].
+pub const CANONICAL_MIN_DECIMAL: f64 = 1e-5;
+pub const CANONICAL_MAX_DECIMAL: f64 = 1e16;
+
+/// Platform-independent rendering of `x`, using the fewest digits that parse back to the identical `f64` (decimal notation for moderate magnitudes, scientific otherwise). `-0` renders as `0`.
+pub fn canonical_f64(x: f64) -> String {
+    if x == 0. {
+        "0".to_string()
+    } else if !x.is_finite() {
+        x.to_string()
+    } else {
+        let abs = x.abs();
+        if (CANONICAL_MIN_DECIMAL..CANONICAL_MAX_DECIMAL).contains(&abs) {
+            x.to_string()
+        } else {
+            format!("{:e}", x)
+        }
+    }
+}
+
+/// Round `x` to `digits` significant (decimal) digits.
+pub fn round_sig(x: f64, digits: usize) -> f64 {
+    if x == 0. || !x.is_finite() || digits == 0 {
+        x
+    } else {
+        format!("{:.*e}", digits - 1, x).parse().unwrap()
+    }
+}
+
 pub trait Fmt {
     fn s(&self, n: usize) -> String;
 }
@@ -15,4 +44,23 @@ impl Fmt for Dual {
     fn s(&self, n: usize) -> String {
         format!("{}, vec![{}]", self.v().s(n), self.d().iter().map(|d| d.s(n)).collect::<Vec<String>>().join(", "))
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn canonical() {
+        for x in [ 0.1 + 0.2, 1. / 3., -2.5, 1e-7 / 3., 6.02214076e23, 1e16, f64::MIN_POSITIVE, f64::MAX, 0.9403281229495638 ] {
+            let s = canonical_f64(x);
+            assert_eq!(s.parse::<f64>().unwrap().to_bits(), x.to_bits(), "{} -> {}", x, s);
+        }
+        assert_eq!(canonical_f64(-0.), "0");
+        assert_eq!(canonical_f64(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(canonical_f64(1e-7 / 4.), "2.5e-8");
+        assert_eq!(canonical_f64(2e20), "2e20");
+        assert_eq!(round_sig(0.1 + 0.2, 15), 0.3);
+        assert_eq!(round_sig(-123456., 2), -120000.);
+    }
+}
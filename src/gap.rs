@@ -15,4 +15,21 @@ where
     fn gap(&self, o: &Shape<D>) -> Option<D> {
         self.apply(&o.projection()).unit_circle_gap()
     }
-}
\ No newline at end of file
+}
+/// How deeply two shapes overlap; the counterpart of [`Gap`] for intersecting shapes.
+pub trait Overlap<O> {
+    type Output;
+    fn overlap(&self, o: &O) -> Option<Self::Output>;
+}
+
+impl<
+    D: LevelArg + UnitCircleGap
+> Overlap<Shape<D>> for Shape<D>
+where
+    Shape<D>: CanTransform<D, Output = Shape<D>> + HasProjection<D>,
+{
+    type Output = D;
+    fn overlap(&self, o: &Shape<D>) -> Option<D> {
+        self.apply(&o.projection()).unit_circle_overlap()
+    }
+}
@@ -11,4 +11,45 @@ where R2<D>: To<R2<f64>>,
         let secant_area = Region::secant_area(&self);
         polygon_area + secant_area
     }
-}
\ No newline at end of file
+}
+
+/// Convex hull of a set of points (Andrew's monotone chain), in counter-clockwise order starting from the lowest point (leftmost, among ties). Collinear points along hull edges are dropped.
+pub fn convex_hull(points: &[R2<f64>]) -> Vec<R2<f64>> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: &R2<f64>, a: &R2<f64>, b: &R2<f64>| (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x);
+    let mut hull: Vec<R2<f64>> = Vec::with_capacity(2 * points.len());
+    // Lower hull, then upper hull
+    for pass in [ points.iter().collect::<Vec<_>>(), points.iter().rev().collect() ] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2 && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], p) <= 0. {
+                hull.pop();
+            }
+            hull.push(*p);
+        }
+        // Last point of each pass is the first point of the next
+        hull.pop();
+    }
+    let start = hull.iter().enumerate().min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))).map(|(idx, _)| idx).unwrap();
+    hull.rotate_left(start);
+    hull
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    fn r2(x: f64, y: f64) -> R2<f64> { R2 { x, y } }
+
+    #[test]
+    fn square() {
+        let points = [ r2(1., 1.), r2(0., 1.), r2(0.5, 0.5), r2(1., 0.), r2(0., 0.), r2(0.5, 0.), r2(0., 0.) ];
+        assert_eq!(convex_hull(&points), vec![ r2(0., 0.), r2(1., 0.), r2(1., 1.), r2(0., 1.) ]);
+        assert_eq!(convex_hull(&points[..2]), vec![ r2(0., 1.), r2(1., 1.) ]);
+    }
+}
@@ -1,8 +1,8 @@
-use std::{ops::{Div, Neg, Add, Mul, Sub}, fmt::Display};
+use std::{ops::{Div, Neg, Add, Mul, Sub}, fmt::Display, f64::consts::PI};
 
 use log::debug;
 
-use crate::{circle, dual::Dual, ellipses::{cdef, xyrrt}, r2::R2, transform::{CanProject, CanTransform, HasProjection}, shape::Shape, trig::Trig, theta_points::ThetaPointsArg, rotate::RotateArg};
+use crate::{circle, dual::Dual, ellipses::{cdef, xyrrt}, r2::R2, transform::{CanProject, CanTransform, HasProjection}, shape::Shape, trig::Trig, theta_points::{ThetaPoints, ThetaPointsArg}, rotate::RotateArg};
 
 pub trait Intersect<In, Out> {
     fn intersect(&self, other: &In) -> Vec<R2<Out>>;
@@ -47,6 +47,103 @@ where
     }
 }
 
+/// Intersection points closer than this are merged by [`Shape::intersection_points`].
+pub const MERGE_EPSILON: f64 = 1e-7;
+
+/// Shapes whose boundaries are within this distance of each other (in each shape's unit-circle projection) at [`COINCIDENT_SAMPLES`] points are considered coincident (see [`Shape::coincident`]).
+pub const COINCIDENT_EPSILON: f64 = 1e-10;
+
+/// Number of boundary points checked by [`Shape::coincident`] (5 points determine a conic, so this includes a margin).
+pub const COINCIDENT_SAMPLES: usize = 8;
+
+/// Boundary intersections of two shapes (see [`Shape::intersections`]).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Intersections<D> {
+    /// The boundaries cross or touch at these (possibly zero) points.
+    Points(Vec<R2<D>>),
+    /// The boundaries coincide (e.g. identical ellipses, or an ellipse and its 180°-rotated copy), so there are infinitely many intersection points.
+    Coincident,
+}
+
+impl Shape<f64> {
+    /// Whether `self` and `o` have the same boundary, even if described by different parameters (e.g. a [`Shape::Circle`] and an equivalent [`Shape::XYRR`], or [`Shape::XYRRT`]s with rotations differing by 180°).
+    pub fn coincident(&self, o: &Shape<f64>) -> bool {
+        let on_boundary = |a: &Shape<f64>, b: &Shape<f64>| {
+            let projection = b.projection();
+            (0..COINCIDENT_SAMPLES).all(|idx| {
+                let t = 2. * PI * (idx as f64) / (COINCIDENT_SAMPLES as f64);
+                (a.point(t).apply(&projection).norm() - 1.).abs() < COINCIDENT_EPSILON
+            })
+        };
+        on_boundary(self, o) && on_boundary(o, self)
+    }
+}
+
+fn f64_distance((x0, y0): (f64, f64), (x1, y1): (f64, f64)) -> f64 {
+    (x1 - x0).hypot(y1 - y0)
+}
+
+impl<D: IntersectShapesArg> Shape<D>
+where
+    R2<D>
+    : Neg<Output = R2<D>>
+    + CanTransform<D, Output = R2<D>>,
+    Shape<D>: CanTransform<D, Output = Shape<D>>,
+    f64
+    : Add<D, Output = D>
+    + Sub<D, Output = D>
+    + Mul<D, Output = D>
+    + Div<D, Output = D>,
+{
+    /// Points where the boundaries of `self` and `o` cross, or [`Intersections::Coincident`] if the boundaries are the same (which the quartic-based intersection can't represent; it returns spurious points or none, depending on the parametrizations).
+    pub fn intersections(&self, o: &Shape<D>) -> Intersections<D>
+    where
+        Shape<f64>: From<Shape<D>>,
+    {
+        let (a, b): (Shape<f64>, Shape<f64>) = (self.clone().into(), o.clone().into());
+        if a.coincident(&b) {
+            debug!("Coincident shapes: {} == {}", a, b);
+            Intersections::Coincident
+        } else {
+            Intersections::Points(self.intersect(o))
+        }
+    }
+    /// Points where the boundaries of `self` and `o` cross, with near-coincident points (e.g. a doubled tangent point) merged. Coincident shapes have no (isolated) intersection points. Pairs of circles use the closed-form [`Circle::intersect`](crate::circle::Circle) instead of the quartic ([`Scene`](crate::scene::Scene)s still use the quartic, via [`Self::intersections`], so that training trajectories are unchanged).
+    pub fn intersection_points(&self, o: &Shape<D>) -> Vec<R2<D>>
+    where
+        Shape<f64>: From<Shape<D>>,
+    {
+        let mut points: Vec<R2<D>> = Vec::new();
+        let intersections = match (self, o) {
+            (Shape::Circle(c0), Shape::Circle(c1)) => {
+                let (r0, r1): (f64, f64) = (c0.r.clone().into(), c1.r.clone().into());
+                let (x0, y0, x1, y1): (f64, f64, f64, f64) = (c0.c.x.clone().into(), c0.c.y.clone().into(), c1.c.x.clone().into(), c1.c.y.clone().into());
+                if f64_distance((x0, y0), (x1, y1)) < COINCIDENT_EPSILON && <f64 as Sub>::sub(r0, r1).abs() < COINCIDENT_EPSILON {
+                    debug!("Coincident circles: {} == {}", c0, c1);
+                    Intersections::Coincident
+                } else {
+                    Intersections::Points(c0.intersect(c1))
+                }
+            },
+            _ => self.intersections(o),
+        };
+        let Intersections::Points(intersections) = intersections else {
+            return points;
+        };
+        for p in intersections {
+            let (x, y): (f64, f64) = (p.x.clone().into(), p.y.clone().into());
+            let is_dupe = points.iter().any(|q| {
+                let (qx, qy): (f64, f64) = (q.x.clone().into(), q.y.clone().into());
+                f64_distance((x, y), (qx, qy)) < MERGE_EPSILON
+            });
+            if !is_dupe {
+                points.push(p);
+            }
+        }
+        points
+    }
+}
+
 pub trait UnitCircleIntersections<D> {
     fn unit_circle_intersections(&self) -> Vec<R2<D>>;
 }
@@ -75,3 +172,66 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::shape::{circle, xyrr, xyrrt};
+
+    use super::*;
+    use test_log::test;
+
+    fn check(a: Shape<f64>, b: Shape<f64>, expected: usize) {
+        let points = a.intersection_points(&b);
+        assert_eq!(points.len(), expected, "{} ∩ {}: {:?}", a, b, points);
+        // Symmetric
+        assert_eq!(b.intersection_points(&a).len(), expected);
+        // Points lie on both boundaries: projecting onto either shape's unit circle gives norm ≈ 1
+        for p in &points {
+            for s in [ &a, &b ] {
+                let n = p.apply(&s.projection()).norm();
+                assert_relative_eq!(n, 1., epsilon = 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn pairs() {
+        check(circle(0., 0., 1.), circle(1., 0., 1.), 2);
+        check(circle(0., 0., 1.), circle(5., 0., 1.), 0);
+        check(circle(0., 0., 1.), xyrr(0., 0., 2., 0.5), 4);
+        check(xyrr(0., 0., 2., 1.), xyrr(1., 1., 1., 2.), 4);
+        check(xyrr(0., 0., 2., 1.), xyrrt(0., 0., 2., 1., 1.), 4);
+        check(xyrrt(0., 0., 2., 1., 0.5), xyrrt(1., 0., 2., 1., -0.5), 4);
+        // Tangent: doubled root is merged
+        check(circle(0., 0., 1.), circle(2., 0., 1.), 1);
+        check(circle(0., 0., 2.), circle(0.5, 0., 1.), 0);
+    }
+
+    #[test]
+    fn degenerate() {
+        let coincident = |a: Shape<f64>, b: Shape<f64>| {
+            assert!(a.coincident(&b) && b.coincident(&a), "{} vs. {}", a, b);
+            assert_eq!(a.intersections(&b), Intersections::Coincident);
+            assert_eq!(a.intersection_points(&b), vec![]);
+        };
+        // Identical
+        coincident(xyrr(0., 0., 2., 1.), xyrr(0., 0., 2., 1.));
+        coincident(xyrrt(1., 1., 2., 1., 0.5), xyrrt(1., 1., 2., 1., 0.5));
+        coincident(circle(0., 0., 1.), circle(0., 0., 1.));
+        // Same boundary, different parametrizations
+        coincident(circle(1., 2., 1.), xyrr(1., 2., 1., 1.));
+        coincident(xyrr(0., 0., 2., 1.), xyrrt(0., 0., 2., 1., PI));
+        coincident(xyrr(0., 0., 2., 1.), xyrrt(0., 0., 1., 2., PI / 2.));
+
+        // Concentric, nested
+        assert!(!xyrr(0., 0., 2., 1.).coincident(&xyrr(0., 0., 1., 0.5)));
+        check(xyrr(0., 0., 2., 1.), xyrr(0., 0., 1., 0.5), 0);
+        // Concentric, tangent at (0, ±1) (doubled roots, merged)
+        check(xyrr(0., 0., 2., 1.), circle(0., 0., 1.), 2);
+        check(xyrr(0., 0., 2., 1.), xyrr(0., 0., 2., 2.), 2);
+        // Concentric, crossing
+        check(xyrr(0., 0., 2., 1.), xyrr(0., 0., 1., 2.), 4);
+    }
+}
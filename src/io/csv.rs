@@ -0,0 +1,182 @@
+use std::io::{BufRead, Write};
+
+use anyhow::Result;
+
+use crate::{fmt::canonical_f64, model::Model, targets::{Targets, TargetsMap}};
+
+/// Header of the key column indicating that keys are membership bitstrings (e.g. `101`: in sets 0 and 2, not 1).
+static MEMBERSHIP_COL: &str = "membership";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TargetsCsvErr {
+    #[error("Expected a header row")]
+    MissingHeader,
+    #[error("Line {0}: expected 2 columns (key, count), found {1}")]
+    InvalidRow(usize, usize),
+    #[error("Line {0}: invalid key {1}")]
+    InvalidKey(usize, String),
+    #[error("Line {0}: key {1} has length {2}, expected {3}")]
+    InconsistentKeyLength(usize, String, usize, usize),
+    #[error("Line {0}: invalid count {1}")]
+    InvalidCount(usize, String),
+    #[error("Line {0}: duplicate key {1}")]
+    DuplicateKey(usize, String),
+    #[error("Region {0} has no target")]
+    UnknownRegion(String),
+}
+use TargetsCsvErr::{MissingHeader, InvalidRow, InvalidKey, InconsistentKeyLength, InvalidCount, DuplicateKey, UnknownRegion};
+
+/// Split a CSV line into trimmed fields, dropping empty trailing fields (e.g. from trailing commas).
+fn fields(line: &str) -> Vec<&str> {
+    let mut fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+    while fields.last() == Some(&"") {
+        fields.pop();
+    }
+    fields
+}
+
+/// Convert a membership bitstring (e.g. `101`) to an exclusive region key (e.g. `0-2`).
+fn membership_key(bits: &str) -> Option<String> {
+    bits.chars().enumerate().map(|(idx, ch)| match ch {
+        '1' => Some(Targets::<f64>::idx(idx)),
+        '0' => Some('-'),
+        _ => None,
+    }).collect()
+}
+
+/// Whether `key` is a valid region key: each position `i` is `i`, `-` (exclusive), or `*` (inclusive).
+fn is_region_key(key: &str) -> bool {
+    key.chars().enumerate().all(|(idx, ch)| ch == '-' || ch == '*' || ch == Targets::<f64>::idx(idx))
+}
+
+/// Parse region targets from a 2-column CSV (key, count) with a header row. If the key column is named `membership`, keys are bitstrings (`10`, `11`, …, converted to exclusive keys); otherwise they are region keys, exclusive (`0-2-`) and/or inclusive (`0*`). Whitespace around fields, trailing commas, and blank lines are ignored.
+pub fn parse_targets_csv<R: BufRead>(reader: R) -> Result<TargetsMap<f64>> {
+    let mut lines = reader.lines().enumerate().map(|(idx, line)| line.map(|line| (idx + 1, line)));
+    let header = loop {
+        match lines.next() {
+            Some(line) => {
+                let (_, line) = line?;
+                if !line.trim().is_empty() {
+                    break line;
+                }
+            }
+            None => return Err(MissingHeader.into()),
+        }
+    };
+    let membership = fields(&header).first().is_some_and(|col| col.eq_ignore_ascii_case(MEMBERSHIP_COL));
+    let mut targets: TargetsMap<f64> = TargetsMap::new();
+    let mut key_len: Option<usize> = None;
+    for line in lines {
+        let (line_no, line) = line?;
+        let row = fields(&line);
+        if row.is_empty() {
+            continue;
+        }
+        if row.len() != 2 {
+            return Err(InvalidRow(line_no, row.len()).into());
+        }
+        let (key, count) = (row[0], row[1]);
+        let key = if membership {
+            membership_key(key).ok_or_else(|| InvalidKey(line_no, key.to_string()))?
+        } else if is_region_key(key) {
+            key.to_string()
+        } else {
+            return Err(InvalidKey(line_no, key.to_string()).into());
+        };
+        let expected = *key_len.get_or_insert(key.len());
+        if key.len() != expected {
+            return Err(InconsistentKeyLength(line_no, key.clone(), key.len(), expected).into());
+        }
+        let count = count.parse::<f64>().map_err(|_| InvalidCount(line_no, count.to_string()))?;
+        if targets.insert(key.clone(), count).is_some() {
+            return Err(DuplicateKey(line_no, key).into());
+        }
+    }
+    Ok(targets)
+}
+
+/// Write `model`'s error curve as CSV: a `step_idx,error,grad_norm` header, then one row per step. If `region` is given, its per-step (signed) error is included as an additional column, named by its key.
+pub fn write_error_curve<W: Write>(model: &Model, region: Option<&str>, mut writer: W) -> Result<()> {
+    if let Some(region) = region {
+        if !model.steps[0].errors.contains_key(region) {
+            return Err(UnknownRegion(region.to_string()).into());
+        }
+    }
+    write!(writer, "step_idx,error,grad_norm")?;
+    if let Some(region) = region {
+        write!(writer, ",{}", region)?;
+    }
+    writeln!(writer)?;
+    for (step_idx, step) in model.steps.iter().enumerate() {
+        let grad_norm = step.error.d().iter().map(|d| d * d).sum::<f64>().sqrt();
+        write!(writer, "{},{},{}", step_idx, canonical_f64(step.error.v()), canonical_f64(grad_norm))?;
+        if let Some(region) = region {
+            write!(writer, ",{}", canonical_f64(step.errors[region].error.v()))?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{duals::{D, Z}, shape::circle, to::To};
+    use test_log::test;
+
+    #[test]
+    fn membership() {
+        let csv = "membership, count\n100, 4\n010,3,\n001 ,2\n\n110, 2\n101,2\n011,1\n111,1\n";
+        let targets = parse_targets_csv(csv.as_bytes()).unwrap();
+        let expected: TargetsMap<f64> = [
+            ("0--", 4.), ("-1-", 3.), ("--2", 2.),
+            ("01-", 2.), ("0-2", 2.), ("-12", 1.),
+            ("012", 1.),
+        ].to();
+        assert_eq!(targets, expected);
+        // Round-trip: exclusive keys (as written by `Targets`) parse back to the same map
+        let exclusive = expected.iter().map(|(k, v)| format!("{},{}", k, v)).collect::<Vec<_>>().join("\n");
+        let reparsed = parse_targets_csv(format!("key,count\n{}", exclusive).as_bytes()).unwrap();
+        assert_eq!(reparsed, expected);
+    }
+
+    #[test]
+    fn inclusive() {
+        let csv = "key,count\n0*,9\n*1,3\n01,1\n";
+        let targets = parse_targets_csv(csv.as_bytes()).unwrap();
+        assert_eq!(targets, [ ("0*", 9.), ("*1", 3.), ("01", 1.) ].to());
+        assert_eq!(Targets::new(targets)["0-"], 8.);
+    }
+
+    #[test]
+    fn error_curve() {
+        let inputs = vec![
+            (circle(0., 0., 1.), vec![ Z, Z, Z, ]),
+            (circle(1., 0., 1.), vec![ D, Z, D, ]),
+        ];
+        let mut model = Model::new(inputs, [ ("0*", 3.), ("*1", 1.), ("01", 0.5) ].to());
+        model.train(0.5, 10);
+        let mut out: Vec<u8> = Vec::new();
+        write_error_curve(&model, Some("01"), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "step_idx,error,grad_norm,01");
+        assert_eq!(lines.len(), model.steps.len() + 1);
+        let last: Vec<f64> = lines[lines.len() - 1].split(',').map(|f| f.parse().unwrap()).collect();
+        let step = model.steps.last().unwrap();
+        assert_eq!(last[0], (model.steps.len() - 1) as f64);
+        assert_eq!(last[1], step.error.v());
+        assert_eq!(last[3], step.errors["01"].error.v());
+        assert!(write_error_curve(&model, Some("0-2"), Vec::new()).is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse_targets_csv("".as_bytes()).is_err());
+        assert!(parse_targets_csv("key,count\n10,1\n".as_bytes()).is_err());
+        assert!(parse_targets_csv("key,count\n0*,1\n012,1\n".as_bytes()).is_err());
+        assert!(parse_targets_csv("key,count\n0*,x\n".as_bytes()).is_err());
+        assert!(parse_targets_csv("key,count\n0*,1,2\n".as_bytes()).is_err());
+        assert!(parse_targets_csv("membership,count\n12,1\n".as_bytes()).is_err());
+    }
+}
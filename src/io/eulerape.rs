@@ -0,0 +1,156 @@
+use std::{collections::BTreeMap, f64::consts::PI, io::BufRead};
+
+use anyhow::Result;
+
+use crate::{shape::{xyrr, xyrrt, InputSpec}, targets::{Targets, TargetsMap}};
+
+#[derive(Debug, thiserror::Error)]
+pub enum EulerApeErr {
+    #[error("Line {0}: expected `ellipse <set> <cx> <cy> <rx> <ry> <angle>` or `<region> <area>`, found {1:?}")]
+    InvalidLine(usize, String),
+    #[error("Line {0}: invalid number {1}")]
+    InvalidNumber(usize, String),
+    #[error("Line {0}: invalid set or region name {1}")]
+    InvalidName(usize, String),
+    #[error("Line {0}: duplicate ellipse for set {1}")]
+    DuplicateEllipse(usize, char),
+    #[error("Line {0}: duplicate region {1}")]
+    DuplicateRegion(usize, String),
+    #[error("No ellipses found")]
+    NoEllipses,
+    #[error("Set {0} has no ellipse (sets must be consecutive letters, starting from `a`)")]
+    MissingEllipse(char),
+    #[error("Region {0} refers to a set with no ellipse")]
+    UnknownSet(String),
+    #[error("Region {0} has no area")]
+    MissingRegion(String),
+}
+use EulerApeErr::{InvalidLine, InvalidNumber, InvalidName, DuplicateEllipse, DuplicateRegion, NoEllipses, MissingEllipse, UnknownSet, MissingRegion};
+
+/// Parse an eulerAPE-style diagram spec (ellipse parameters and exclusive region areas) into apvd inputs and (exclusive) region targets. The format is line-based:
+///
+/// ```text
+/// # Comments (from `#`) and blank lines are ignored
+/// ellipse a 0 0 1.2 0.8 0
+/// ellipse b 1 0.2 1 0.7 -30
+/// a 1.8
+/// b 1.5
+/// ab 0.6
+/// ```
+///
+/// - `ellipse <set> <cx> <cy> <rx> <ry> <angle>` gives set `<set>`'s ellipse: center, semi-axes, and rotation in degrees.
+/// - `<region> <area>` gives the area of the (exclusive) region inside exactly the sets named by `<region>`.
+///
+/// Sets are named by consecutive lowercase letters (`a`, `b`, `c`, …), and become shapes `0`, `1`, `2`, …; region `ab` is apvd's exclusive key `01-` (with 3 sets), and every non-empty region must be given.
+///
+/// eulerAPE uses screen coordinates: `y` grows downward, and positive angles rotate clockwise (on screen). apvd's `y` grows upward, with counter-clockwise angles in radians, so each ellipse is reflected across the `x`-axis: `(cx, cy, θ°)` becomes `(cx, -cy, -θ·π/180)`. Region areas are unaffected by the reflection. Unrotated ellipses become [`XYRR`](crate::ellipses::xyrr::XYRR)s, others [`XYRRT`](crate::ellipses::xyrrt::XYRRT)s; all coordinates are trainable.
+pub fn parse<R: BufRead>(reader: R) -> Result<(Vec<InputSpec>, TargetsMap<f64>)> {
+    let mut ellipses: BTreeMap<char, InputSpec> = BTreeMap::new();
+    let mut regions: BTreeMap<String, f64> = BTreeMap::new();
+    for (idx, line) in reader.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line?;
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let num = |field: &str| field.parse::<f64>().map_err(|_| InvalidNumber(line_no, field.to_string()));
+        match fields[..] {
+            [ "ellipse", set, cx, cy, rx, ry, angle ] => {
+                let set = match set.chars().collect::<Vec<_>>()[..] {
+                    [ ch ] if ch.is_ascii_lowercase() => ch,
+                    _ => return Err(InvalidName(line_no, set.to_string()).into()),
+                };
+                let (cx, cy, rx, ry, angle) = (num(cx)?, -num(cy)?, num(rx)?, num(ry)?, -num(angle)?);
+                let spec: InputSpec = if angle == 0. {
+                    (xyrr(cx, cy, rx, ry), vec![ true; 4 ])
+                } else {
+                    (xyrrt(cx, cy, rx, ry, angle * PI / 180.), vec![ true; 5 ])
+                };
+                if ellipses.insert(set, spec).is_some() {
+                    return Err(DuplicateEllipse(line_no, set).into());
+                }
+            },
+            [ region, area ] => {
+                let mut chars: Vec<char> = region.chars().collect();
+                chars.sort();
+                chars.dedup();
+                if chars.len() != region.len() || !chars.iter().all(|ch| ch.is_ascii_lowercase()) {
+                    return Err(InvalidName(line_no, region.to_string()).into());
+                }
+                if regions.insert(chars.into_iter().collect(), num(area)?).is_some() {
+                    return Err(DuplicateRegion(line_no, region.to_string()).into());
+                }
+            },
+            _ => return Err(InvalidLine(line_no, line.to_string()).into()),
+        }
+    }
+    let n = ellipses.len();
+    if n == 0 {
+        return Err(NoEllipses.into());
+    }
+    let sets: Vec<char> = ('a'..='z').take(n).collect();
+    if let Some(missing) = sets.iter().find(|set| !ellipses.contains_key(set)) {
+        return Err(MissingEllipse(*missing).into());
+    }
+    if let Some(region) = regions.keys().find(|region| region.chars().any(|ch| !sets.contains(&ch))) {
+        return Err(UnknownSet(region.clone()).into());
+    }
+    let mut targets: TargetsMap<f64> = TargetsMap::new();
+    for mask in 1..(1usize << n) {
+        let region: String = sets.iter().enumerate().filter(|(idx, _)| mask & (1 << idx) != 0).map(|(_, set)| *set).collect();
+        let area = *regions.get(&region).ok_or_else(|| MissingRegion(region.clone()))?;
+        let key: String = (0..n).map(|idx| if mask & (1 << idx) != 0 { Targets::<f64>::idx(idx) } else { '-' }).collect();
+        targets.insert(key, area);
+    }
+    Ok((ellipses.into_values().collect(), targets))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs::File, io::BufReader};
+    use crate::{shape::Shape, step::Step};
+    use test_log::test;
+
+    #[test]
+    fn three_ellipses() {
+        let file = File::open("testdata/eulerape/three_ellipses.txt").unwrap();
+        let (inputs, targets) = parse(BufReader::new(file)).unwrap();
+        assert_eq!(inputs.len(), 3);
+        assert_eq!(targets.len(), 7);
+        assert_eq!(targets["01-"], 0.6);
+        assert_eq!(targets["012"], 0.25);
+        // Unrotated ellipse: reflected across the x-axis
+        assert_eq!(inputs[0], (xyrr(0., -0.1, 1.2, 0.8), vec![ true; 4 ]));
+        // Rotated ellipse: reflection also negates the angle
+        match &inputs[1].0 {
+            Shape::XYRRT(e) => {
+                assert_eq!((e.c.x, e.c.y, e.r.x, e.r.y), (1., -0.2, 1., 0.7));
+                assert_relative_eq!(e.t, PI / 6., epsilon = 1e-15);
+            },
+            shape => panic!("Expected XYRRT, found {:?}", shape),
+        }
+        assert_eq!(inputs[1].1.len(), 5);
+        // Parsed shapes and targets can seed a model
+        let step = Step::new(inputs, targets.into());
+        assert!(step.error.v().is_finite());
+    }
+
+    #[test]
+    fn invalid() {
+        let parse_str = |s: &str| parse(s.as_bytes());
+        assert!(parse_str("").is_err());
+        assert!(parse_str("ellipse a 0 0 1 1\n").is_err());
+        assert!(parse_str("ellipse a 0 0 1 x 0\na 1\n").is_err());
+        assert!(parse_str("ellipse A 0 0 1 1 0\na 1\n").is_err());
+        assert!(parse_str("ellipse a 0 0 1 1 0\nellipse a 1 0 1 1 0\na 1\n").is_err());
+        assert!(parse_str("ellipse a 0 0 1 1 0\nellipse c 1 0 1 1 0\na 1\nc 1\nac 1\n").is_err());
+        assert!(parse_str("ellipse a 0 0 1 1 0\na 1\nb 1\n").is_err());
+        assert!(parse_str("ellipse a 0 0 1 1 0\nellipse b 1 0 1 1 0\na 1\nb 1\n").is_err());
+        assert!(parse_str("ellipse a 0 0 1 1 0\na 1\na 2\n").is_err());
+        assert!(parse_str("ellipse a 0 0 1 1 0\naa 1\n").is_err());
+        assert!(parse_str("ellipse a 0 0 1 1 0\na 1\n").is_ok());
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{step::Step, targets::Targets};
+
+/// One element of a venn.js input array: the (inclusive) area of the intersection of `sets`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+pub struct VennJsEntry {
+    pub sets: Vec<String>,
+    pub size: f64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum VennJsErr {
+    #[error("Expected {0} labels, found {1}: {2:?}")]
+    LabelCount(usize, usize, Vec<String>),
+}
+
+/// venn.js-style `[{ sets, size }]` entries for every non-empty combination of sets in `step`, with sizes computed from its region areas. Sets are named by `labels`, or else by the targets' [`Targets::labels`], or else by their indices; it's an error for there to be a different number of labels than shapes.
+pub fn vennjs(step: &Step, labels: Option<&[String]>) -> Result<Vec<VennJsEntry>> {
+    let n = step.shapes.len();
+    let labels = labels.or_else(|| Some(&step.targets.labels[..]).filter(|labels| !labels.is_empty()));
+    if let Some(labels) = labels {
+        if labels.len() != n {
+            return Err(VennJsErr::LabelCount(n, labels.len(), labels.to_vec()).into());
+        }
+    }
+    let label = |idx: usize| labels.map_or_else(|| idx.to_string(), |labels| labels[idx].clone());
+    let region_areas = step.region_areas();
+    Ok((1..(1usize << n)).map(|mask| {
+        let members: Vec<usize> = (0..n).filter(|idx| mask & (1 << idx) != 0).collect();
+        let size = region_areas.iter().filter(|region| {
+            members.iter().all(|&idx| region.key.chars().nth(idx) == Some(Targets::<f64>::idx(idx)))
+        }).map(|region| region.area).sum();
+        VennJsEntry { sets: members.into_iter().map(label).collect(), size }
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{duals::{D, Z}, shape::circle, to::To, targets::TargetsMap};
+    use test_log::test;
+
+    #[test]
+    fn two_circles() {
+        let targets: TargetsMap<f64> = [ ("0*", 3.), ("*1", 1.), ("01", 0.5) ].to();
+        let step = Step::new(
+            vec![
+                (circle(0., 0., 1.), vec![ Z, Z, Z, ]),
+                (circle(1., 0., 1.), vec![ D, Z, D, ]),
+            ],
+            targets.into(),
+        );
+        let labels = [ "A".to_string(), "B".to_string() ];
+        let entries = vennjs(&step, Some(&labels)).unwrap();
+        assert_eq!(entries.iter().map(|e| e.sets.join("∩")).collect::<Vec<_>>(), vec![ "A", "B", "A∩B" ]);
+        let areas = step.region_areas();
+        let area = |key: &str| areas.iter().find(|r| r.key == key).unwrap().area;
+        assert_relative_eq!(entries[0].size, area("0-") + area("01"), epsilon = 1e-12);
+        assert_relative_eq!(entries[1].size, area("-1") + area("01"), epsilon = 1e-12);
+        assert_eq!(entries[2].size, area("01"));
+        assert_eq!(vennjs(&step, None).unwrap()[2].sets, vec![ "0", "1" ]);
+        let err = vennjs(&step, Some(&labels[..1])).unwrap_err();
+        assert_eq!(err.to_string(), r#"Expected 2 labels, found 1: ["A"]"#);
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::VecDeque;
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+/// Limited-memory BFGS state: the most recent `history_size` (position-delta, gradient-delta) pairs, used to approximate the inverse Hessian.
+#[derive(Clone, Debug)]
+pub struct Lbfgs {
+    pub history_size: usize,
+    pub history: VecDeque<(Vec<f64>, Vec<f64>)>,
+}
+
+impl Lbfgs {
+    pub fn new(history_size: usize) -> Lbfgs {
+        Lbfgs { history_size, history: VecDeque::with_capacity(history_size) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    /// Record a step `s` (change in coordinates) and the resulting change in gradient `y`. Pairs that don't satisfy the curvature condition (`s·y > 0`) are dropped, since they would make the inverse-Hessian approximation indefinite.
+    pub fn update(&mut self, s: Vec<f64>, y: Vec<f64>) {
+        if self.history_size == 0 {
+            return;
+        }
+        let sy = dot(&s, &y);
+        if sy <= 1e-12 * dot(&y, &y).sqrt() * dot(&s, &s).sqrt() || !sy.is_finite() {
+            return;
+        }
+        if self.history.len() == self.history_size {
+            self.history.pop_front();
+        }
+        self.history.push_back((s, y));
+    }
+
+    /// Two-loop recursion: approximate `-H⁻¹·grad`.
+    pub fn direction(&self, grad: &[f64]) -> Vec<f64> {
+        let mut q = grad.to_vec();
+        let mut alphas = Vec::with_capacity(self.history.len());
+        for (s, y) in self.history.iter().rev() {
+            let rho = 1. / dot(y, s);
+            let alpha = rho * dot(s, &q);
+            q.iter_mut().zip(y.iter()).for_each(|(q, y)| *q -= alpha * y);
+            alphas.push((rho, alpha));
+        }
+        if let Some((s, y)) = self.history.back() {
+            let gamma = dot(s, y) / dot(y, y);
+            q.iter_mut().for_each(|q| *q *= gamma);
+        }
+        for ((s, y), (rho, alpha)) in self.history.iter().zip(alphas.into_iter().rev()) {
+            let beta = rho * dot(y, &q);
+            q.iter_mut().zip(s.iter()).for_each(|(q, s)| *q += (alpha - beta) * s);
+        }
+        q.iter().map(|q| -q).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn quadratic() {
+        // f(x) = ½ xᵀAx, A = diag(1, 10); gradient Ax. After observing both axes, the direction should be ≈ -x.
+        let a = [1., 10.];
+        let grad = |x: &[f64]| x.iter().zip(a.iter()).map(|(x, a)| x * a).collect::<Vec<f64>>();
+        let mut lbfgs = Lbfgs::new(5);
+        let empty = lbfgs.direction(&[1., 1.]);
+        assert_eq!(empty, vec![-1., -1.]);
+        let pts = [vec![1., 1.], vec![0.5, 1.], vec![0.5, 0.5]];
+        for w in pts.windows(2) {
+            let s = w[1].iter().zip(w[0].iter()).map(|(a, b)| a - b).collect();
+            let y = grad(&w[1]).iter().zip(grad(&w[0]).iter()).map(|(a, b)| a - b).collect();
+            lbfgs.update(s, y);
+        }
+        let x = [0.5, 0.5];
+        let d = lbfgs.direction(&grad(&x));
+        assert!((d[0] + 0.5).abs() < 1e-9, "{:?}", d);
+        assert!((d[1] + 0.5).abs() < 1e-9, "{:?}", d);
+    }
+}
@@ -4,6 +4,8 @@
 extern crate approx;
 extern crate console_error_panic_hook;
 
+pub mod bounding_box;
+pub mod bounds;
 pub mod circle;
 pub mod component;
 pub mod contains;
@@ -23,15 +25,27 @@ pub mod history;
 pub mod hull;
 pub mod intersect;
 pub mod intersection;
+pub mod io;
+pub mod lbfgs;
+pub mod loss;
 pub mod node;
+pub mod path;
 pub mod math;
 pub mod model;
 pub mod r2;
+pub mod rmsprop;
+pub mod rng;
 pub mod region;
+pub mod region_geometry;
 pub mod regions;
 pub mod roots;
 pub mod rotate;
+pub mod scanline;
 pub mod scene;
+pub mod schedule;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod seed;
 pub mod segment;
 pub mod set;
 pub mod shape;
@@ -46,16 +60,25 @@ pub mod zero;
 pub mod js_dual;
 
 use targets::Targets;
-use shape::InputSpec;
+use shape::{InputSpec, Shape, ShapeKind};
 use step::Step;
+use scene::Scene;
+use r2::R2;
 use dual::D;
 use ellipses::xyrr::XYRR;
 use log::{LevelFilter, info, error};
+use std::{collections::BTreeMap, sync::{Arc, atomic::{AtomicBool, Ordering}}};
 
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_console_logger::DEFAULT_LOGGER;
-use crate::targets::TargetsMap;
-use crate::model::Model;
+use crate::targets::{TargetSpecs, TargetsMap};
+use crate::model::{CompactModel, Model};
+use crate::schedule::LearningRateSchedule;
+use crate::rmsprop::RmsPropConfig;
+use crate::bounds::Bounds;
+use crate::loss::LossConfig;
 
 pub fn deser_log_level(level: JsValue) -> LevelFilter {
     let level: Option<String> = serde_wasm_bindgen::from_value(level).unwrap();
@@ -88,19 +111,84 @@ pub fn update_log_level(level: JsValue) {
 #[wasm_bindgen]
 pub fn make_step(inputs: JsValue, targets: JsValue) -> JsValue {
     let inputs: Vec<InputSpec> = serde_wasm_bindgen::from_value(inputs).unwrap();
-    let targets: TargetsMap<f64> = serde_wasm_bindgen::from_value(targets.clone()).unwrap();
+    let targets: TargetSpecs = serde_wasm_bindgen::from_value(targets.clone()).unwrap();
     let step = Step::new(inputs, targets.into());
     serde_wasm_bindgen::to_value(&step).unwrap()
 }
 
+#[wasm_bindgen]
+pub fn error_only(inputs: JsValue, targets: JsValue) -> f64 {
+    let inputs: Vec<InputSpec> = serde_wasm_bindgen::from_value(inputs).unwrap();
+    let targets: TargetSpecs = serde_wasm_bindgen::from_value(targets.clone()).unwrap();
+    Step::error_only(inputs, targets.into()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn compute_areas(inputs: JsValue) -> JsValue {
+    let inputs: Vec<InputSpec> = serde_wasm_bindgen::from_value(inputs).unwrap();
+    serde_wasm_bindgen::to_value(&step::compute_areas(inputs).unwrap()).unwrap()
+}
+
 #[wasm_bindgen]
 pub fn make_model(inputs: JsValue, targets: JsValue) -> JsValue {
     let inputs: Vec<InputSpec> = serde_wasm_bindgen::from_value(inputs).unwrap();
-    let targets: TargetsMap<f64> = serde_wasm_bindgen::from_value(targets.clone()).unwrap();
-    let model = Model::new(inputs, targets);
+    let targets: TargetSpecs = serde_wasm_bindgen::from_value(targets.clone()).unwrap();
+    let model = Model::from_targets(inputs, targets.into(), LossConfig::default());
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn make_labeled_model(inputs: JsValue, targets: JsValue, labels: JsValue) -> JsValue {
+    let inputs: Vec<InputSpec> = serde_wasm_bindgen::from_value(inputs).unwrap();
+    let targets: TargetSpecs = serde_wasm_bindgen::from_value(targets).unwrap();
+    let labels: Vec<String> = serde_wasm_bindgen::from_value(labels).unwrap();
+    let targets = Targets::from_labeled_specs(labels, targets).unwrap();
+    let model = Model::from_targets(inputs, targets, LossConfig::default());
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn make_model_with_loss(inputs: JsValue, targets: JsValue, relative_weight: f64) -> JsValue {
+    let inputs: Vec<InputSpec> = serde_wasm_bindgen::from_value(inputs).unwrap();
+    let targets: TargetSpecs = serde_wasm_bindgen::from_value(targets.clone()).unwrap();
+    let model = Model::from_targets(inputs, targets.into(), LossConfig { relative_weight, ..LossConfig::default() });
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn make_model_absolute(inputs: JsValue, targets: JsValue, total_area: f64) -> JsValue {
+    let inputs: Vec<InputSpec> = serde_wasm_bindgen::from_value(inputs).unwrap();
+    let targets: TargetSpecs = serde_wasm_bindgen::from_value(targets.clone()).unwrap();
+    let model = Model::from_targets(inputs, targets.into(), LossConfig { total_area: Some(total_area), ..LossConfig::default() });
     serde_wasm_bindgen::to_value(&model).unwrap()
 }
 
+#[wasm_bindgen]
+pub fn make_model_with_threshold(inputs: JsValue, targets: JsValue, threshold: f64) -> JsValue {
+    let inputs: Vec<InputSpec> = serde_wasm_bindgen::from_value(inputs).unwrap();
+    let targets: TargetSpecs = serde_wasm_bindgen::from_value(targets.clone()).unwrap();
+    let model = Model::from_targets(inputs, targets.into(), LossConfig::default()).with_threshold(threshold);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn model_to_compact(model: JsValue) -> JsValue {
+    let model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    serde_wasm_bindgen::to_value(&model.to_compact()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn model_from_compact(compact: JsValue) -> JsValue {
+    let compact: CompactModel = serde_wasm_bindgen::from_value(compact).unwrap();
+    serde_wasm_bindgen::to_value(&compact.rehydrate()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn compact_step(compact: JsValue, idx: usize) -> JsValue {
+    let compact: CompactModel = serde_wasm_bindgen::from_value(compact).unwrap();
+    serde_wasm_bindgen::to_value(&compact.step(idx)).unwrap()
+}
+
 #[wasm_bindgen]
 pub fn train(model: JsValue, max_step_error_ratio: f64, max_steps: usize) -> JsValue {
     let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
@@ -108,6 +196,154 @@ pub fn train(model: JsValue, max_step_error_ratio: f64, max_steps: usize) -> JsV
     serde_wasm_bindgen::to_value(&model).unwrap()
 }
 
+#[wasm_bindgen]
+pub fn train_cached(model: JsValue, max_step_error_ratio: f64, max_steps: usize) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    model.train_cached(max_step_error_ratio, max_steps);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn train_scheduled(model: JsValue, schedule: JsValue, max_steps: usize) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    let schedule: LearningRateSchedule = serde_wasm_bindgen::from_value(schedule).unwrap();
+    model.train_scheduled(schedule, max_steps);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn train_until(model: JsValue, max_step_error_ratio: f64, max_steps: usize, grad_tol: f64) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    model.train_until(max_step_error_ratio, max_steps, grad_tol);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn train_annealed(model: JsValue, max_step_error_ratio: f64, max_steps: usize, restarts: usize, perturb_scale: f64, seed: u64) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    model.train_annealed(max_step_error_ratio, max_steps, restarts, perturb_scale, seed);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn train_multistart(inputs: JsValue, targets: JsValue, n_starts: usize, jitter: f64, max_step_error_ratio: f64, max_steps: usize, seed: u64) -> JsValue {
+    let inputs: Vec<InputSpec> = serde_wasm_bindgen::from_value(inputs).unwrap();
+    let targets: TargetsMap<f64> = serde_wasm_bindgen::from_value(targets).unwrap();
+    let multistart = Model::train_multistart(inputs, targets, n_starts, jitter, max_step_error_ratio, max_steps, seed);
+    serde_wasm_bindgen::to_value(&multistart).unwrap()
+}
+
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn train_multistart_locked(inputs: JsValue, targets: JsValue, locked_areas: JsValue, n_starts: usize, jitter: f64, max_step_error_ratio: f64, max_steps: usize, seed: u64) -> JsValue {
+    let inputs: Vec<InputSpec> = serde_wasm_bindgen::from_value(inputs).unwrap();
+    let targets: TargetsMap<f64> = serde_wasm_bindgen::from_value(targets).unwrap();
+    let locked_areas: BTreeMap<usize, f64> = serde_wasm_bindgen::from_value(locked_areas).unwrap();
+    let multistart = Model::train_multistart_locked(inputs, targets, &locked_areas, n_starts, jitter, max_step_error_ratio, max_steps, seed);
+    serde_wasm_bindgen::to_value(&multistart).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn train_rmsprop(model: JsValue, lr: f64, max_steps: usize, config: JsValue) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    let config: Option<RmsPropConfig> = serde_wasm_bindgen::from_value(config).unwrap();
+    model.train_rmsprop(lr, max_steps, config.unwrap_or_default());
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn train_bounded(model: JsValue, max_step_error_ratio: f64, max_steps: usize, bounds: JsValue) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    let bounds: Bounds = serde_wasm_bindgen::from_value(bounds).unwrap();
+    model.train_bounded(max_step_error_ratio, max_steps, bounds);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+/// Lightweight per-step payload passed to the `on_step` callback of [`train_streaming`].
+#[derive(Debug, Clone, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepUpdate {
+    pub step_index: usize,
+    pub error: f64,
+    pub shapes: Vec<Shape<f64>>,
+}
+
+/// Train like [`train`], but call `on_step` with a [`StepUpdate`] every `every` steps, and only retain the best and latest steps in the returned model (see [`Model::train_streaming`]). The callback runs synchronously on the calling (main) thread; exceptions it throws are logged and training continues.
+#[wasm_bindgen]
+pub fn train_streaming(model: JsValue, max_step_error_ratio: f64, max_steps: usize, every: usize, on_step: &js_sys::Function) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    let every = every.max(1);
+    model.train_streaming(max_step_error_ratio, max_steps, |step_index, step| {
+        if step_index % every == 0 {
+            let update = StepUpdate { step_index, error: step.error.v(), shapes: step.shapes.iter().map(|s| s.v()).collect() };
+            if let Err(err) = on_step.call1(&JsValue::NULL, &serde_wasm_bindgen::to_value(&update).unwrap()) {
+                error!("train_streaming: on_step callback threw at step {}: {:?}", step_index, err);
+            }
+        }
+    });
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+/// Shared stop flag for [`train_cancellable`]. Training runs synchronously, so the flag is only observed if it's set during training, e.g. from a [`train_streaming`] callback, or from another thread/worker sharing the handle.
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct TrainHandle {
+    stop: Arc<AtomicBool>,
+}
+
+#[wasm_bindgen]
+pub fn make_train_handle() -> TrainHandle {
+    TrainHandle::default()
+}
+
+#[wasm_bindgen]
+pub fn request_stop(handle: &TrainHandle) {
+    handle.stop.store(true, Ordering::Relaxed);
+}
+
+#[wasm_bindgen]
+pub fn train_cancellable(model: JsValue, max_step_error_ratio: f64, max_steps: usize, handle: &TrainHandle) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    model.train_cancellable(max_step_error_ratio, max_steps, &handle.stop);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn train_lbfgs(model: JsValue, max_steps: usize, history_size: usize) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    model.train_lbfgs(max_steps, history_size);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn train_newton_sq_error(model: JsValue, max_steps: usize, damping: f64) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    model.train_newton_sq_error(max_steps, damping);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn set_trainable(model: JsValue, shape_idx: usize, coord_mask: JsValue) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    let coord_mask: Vec<bool> = serde_wasm_bindgen::from_value(coord_mask).unwrap();
+    model.set_trainable(shape_idx, coord_mask);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn lock_area(model: JsValue, shape_idx: usize, area: f64) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    model.lock_area(shape_idx, area);
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn promote_rotatable(model: JsValue) -> JsValue {
+    let mut model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    model.promote_to_rotatable();
+    serde_wasm_bindgen::to_value(&model).unwrap()
+}
+
 #[wasm_bindgen]
 pub fn step(step: JsValue, max_step_error_ratio: f64) -> JsValue {
     let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
@@ -116,6 +352,106 @@ pub fn step(step: JsValue, max_step_error_ratio: f64) -> JsValue {
     step
 }
 
+#[wasm_bindgen]
+pub fn step_clipped(step: JsValue, lr: f64, per_coord: f64, max: f64) -> JsValue {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    serde_wasm_bindgen::to_value(&step.step_clipped(lr, per_coord, max)).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn step_clipped_l2(step: JsValue, lr: f64, max_norm: f64) -> JsValue {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    serde_wasm_bindgen::to_value(&step.step_clipped_l2(lr, max_norm)).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn shape_bounds(shape: JsValue) -> JsValue {
+    let shape: Shape<f64> = serde_wasm_bindgen::from_value(shape).unwrap();
+    serde_wasm_bindgen::to_value(&shape.bounding_box()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn shape_perimeter(shape: JsValue) -> f64 {
+    let shape: Shape<f64> = serde_wasm_bindgen::from_value(shape).unwrap();
+    shape.perimeter()
+}
+
+#[wasm_bindgen]
+pub fn convex_hull(shapes: JsValue) -> JsValue {
+    let shapes: Vec<Shape<f64>> = serde_wasm_bindgen::from_value(shapes).unwrap();
+    let scene = Scene::new(shapes);
+    serde_wasm_bindgen::to_value(&scene.convex_hull()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn region_areas(step: JsValue) -> JsValue {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    serde_wasm_bindgen::to_value(&step.region_areas()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn fit_metrics(step: JsValue) -> JsValue {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    serde_wasm_bindgen::to_value(&step.fit_metrics()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn error_breakdown(step: JsValue) -> JsValue {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    serde_wasm_bindgen::to_value(&step.error_breakdown()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn region_keys(step: JsValue) -> JsValue {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    serde_wasm_bindgen::to_value(&step.region_keys()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn region_svg_paths(step: JsValue) -> JsValue {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    serde_wasm_bindgen::to_value(&step.region_svg_paths()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn named_gradients(step: JsValue) -> JsValue {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    serde_wasm_bindgen::to_value(&step.named_gradients()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn model_snapshot(model: JsValue, step_idx: Option<usize>, freeze: bool) -> JsValue {
+    let model: Model = serde_wasm_bindgen::from_value(model).unwrap();
+    serde_wasm_bindgen::to_value(&model.snapshot(step_idx, freeze)).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn region_at(step: JsValue, x: f64, y: f64) -> Option<String> {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    let scene = Scene::new(step.shapes.iter().map(|s| s.v()).collect());
+    scene.region_at(R2 { x, y })
+}
+
+#[wasm_bindgen]
+pub fn step_summary(step: JsValue) -> JsValue {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    serde_wasm_bindgen::to_value(&step.summary()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn containment(shapes: JsValue) -> JsValue {
+    let shapes: Vec<Shape<f64>> = serde_wasm_bindgen::from_value(shapes).unwrap();
+    let scene = Scene::new(shapes);
+    serde_wasm_bindgen::to_value(&scene.containment()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn shape_intersections(a: JsValue, b: JsValue) -> JsValue {
+    let a: Shape<f64> = serde_wasm_bindgen::from_value(a).unwrap();
+    let b: Shape<f64> = serde_wasm_bindgen::from_value(b).unwrap();
+    serde_wasm_bindgen::to_value(&a.intersection_points(&b)).unwrap()
+}
+
 #[wasm_bindgen]
 pub fn expand_targets(targets: JsValue) -> JsValue {
     let targets: TargetsMap<f64> = serde_wasm_bindgen::from_value(targets.clone()).unwrap();
@@ -123,6 +459,35 @@ pub fn expand_targets(targets: JsValue) -> JsValue {
     serde_wasm_bindgen::to_value(&targets).unwrap()
 }
 
+#[wasm_bindgen]
+pub fn seed_layout(targets: JsValue, kind: JsValue) -> JsValue {
+    let targets: TargetsMap<f64> = serde_wasm_bindgen::from_value(targets).unwrap();
+    let kind: ShapeKind = serde_wasm_bindgen::from_value(kind).unwrap();
+    serde_wasm_bindgen::to_value(&Model::seed_layout(&targets, kind)).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn vennjs(step: JsValue, labels: JsValue) -> Result<JsValue, JsError> {
+    let step: Step = serde_wasm_bindgen::from_value(step).unwrap();
+    let labels: Option<Vec<String>> = serde_wasm_bindgen::from_value(labels).unwrap();
+    let entries = io::vennjs::vennjs(&step, labels.as_deref()).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(serde_wasm_bindgen::to_value(&entries).unwrap())
+}
+
+#[wasm_bindgen]
+pub fn targets_summary(targets: JsValue) -> JsValue {
+    let targets: TargetSpecs = serde_wasm_bindgen::from_value(targets).unwrap();
+    let targets: Targets<f64> = targets.into();
+    serde_wasm_bindgen::to_value(&targets.summary()).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn validate_targets(targets: JsValue) -> JsValue {
+    let targets: TargetSpecs = serde_wasm_bindgen::from_value(targets).unwrap();
+    let targets: Targets<f64> = targets.into();
+    serde_wasm_bindgen::to_value(&targets.validate()).unwrap()
+}
+
 #[wasm_bindgen]
 pub fn xyrr_unit(xyrr: JsValue) -> JsValue {
     let xyrr: XYRR<D> = serde_wasm_bindgen::from_value(xyrr).unwrap();
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+/// Targets (as fractions of the total target area) smaller than this are treated as this size when computing relative errors.
+pub const RELATIVE_ERROR_MIN_TARGET_FRAC: f64 = 1e-3;
+
+/// How each region's error is computed from its actual and target areas (see [`crate::step::Step::compute_errors`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LossConfig {
+    /// Mix of absolute and relative error: each region's error is `(1-w)·(actual-target)/total + w·(actual-target)/max(target, ε)` (areas as fractions of their totals). `0` (the default) is purely absolute, and weights all regions' errors equally regardless of their size; increasing it favors fitting small regions.
+    #[serde(default)]
+    pub relative_weight: f64,
+    /// Penalize missing regions whose member shapes are separated by the gaps between them (see [`crate::step::Step::missing_region_gaps`]), instead of by distances to their centroid. Gap gradients include every coordinate that affects the separation (e.g. ellipse rotations).
+    #[serde(default)]
+    pub gap_penalty: bool,
+    /// Treat slightly-negative region areas (floating-point noise) as 0 when computing errors (see [`crate::scene::Scene::area_clamped`]).
+    #[serde(default)]
+    pub clamp_negative_areas: bool,
+    /// Push apart shapes that overlap, but whose intersection's target is 0, in proportion to how deeply they overlap (see [`crate::step::region_overlap`]). Area errors alone give vanishing gradients as such an overlap shrinks.
+    #[serde(default)]
+    pub disjoint_penalty: bool,
+    /// Target for the diagram's total area (in the same units as the shapes). Region errors only depend on areas as fractions of the total, leaving the diagram's overall size free; when set, the loss also includes the total area's relative error `|actual-target|/target`, so that the diagram's areas approach absolute targets.
+    #[serde(default)]
+    pub total_area: Option<f64>,
+}
+
+impl LossConfig {
+    /// Factor by which to scale a region's absolute (fractional) error, given its target fraction.
+    pub fn scale(&self, target_frac: f64) -> f64 {
+        let w = self.relative_weight;
+        (1. - w) + w / target_frac.max(RELATIVE_ERROR_MIN_TARGET_FRAC)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_config() {
+        let loss: LossConfig = serde_json::from_str(r#"{ "gapPenalty": true }"#).unwrap();
+        assert_eq!(loss, LossConfig { gap_penalty: true, ..LossConfig::default() });
+    }
+}
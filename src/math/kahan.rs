@@ -0,0 +1,65 @@
+use std::{iter::{empty, Sum}, ops::{Add, Sub}};
+
+/// Compensated (Kahan) summation: carry the low-order bits lost by each addition in a running compensation term, and fold them back into the next addend. Keeps the accumulated rounding error O(ε), independent of the number of terms (naive summation's grows with the number of terms). Works for any type with `+`/`-`, including [`crate::dual::Dual`]s (whose partial derivatives are compensated along with their values).
+pub trait KahanSum: Iterator + Sized
+where
+    Self::Item: Clone + Add<Output = Self::Item> + Sub<Output = Self::Item>,
+{
+    /// Compensated sum of the items, or `None` if there are none.
+    fn kahan_sum_opt(mut self) -> Option<Self::Item> {
+        let first = self.next()?;
+        let mut compensation: Option<Self::Item> = None;
+        let mut sum = first;
+        for item in self {
+            let y = match compensation {
+                Some(c) => item - c,
+                None => item,
+            };
+            let t = sum.clone() + y.clone();
+            compensation = Some((t.clone() - sum) - y);
+            sum = t;
+        }
+        Some(sum)
+    }
+    /// Compensated sum of the items; empty iterators sum to `Sum`'s empty value.
+    fn kahan_sum(self) -> Self::Item
+    where
+        Self::Item: Sum,
+    {
+        self.kahan_sum_opt().unwrap_or_else(|| empty::<Self::Item>().sum())
+    }
+}
+
+impl<I: Iterator> KahanSum for I
+where
+    I::Item: Clone + Add<Output = I::Item> + Sub<Output = I::Item>,
+{}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    use crate::dual::Dual;
+
+    #[test]
+    fn compensated() {
+        // 1 + 10⁶ × 1e-16: each tiny addend is lost to rounding in a naive sum
+        let terms = || std::iter::once(1.).chain(std::iter::repeat_n(1e-16, 1_000_000));
+        let naive: f64 = terms().sum();
+        assert_eq!(naive, 1.);
+        let kahan = terms().kahan_sum();
+        assert!((kahan - (1. + 1e-10)).abs() < 1e-15, "{}", kahan);
+        assert_eq!(empty::<f64>().kahan_sum(), 0.);
+        assert_eq!(empty::<f64>().kahan_sum_opt(), None);
+    }
+
+    #[test]
+    fn duals() {
+        let terms = || std::iter::once(Dual::new(1., vec![1., 0.])).chain(std::iter::repeat_n(Dual::new(1e-16, vec![0., 1e-16]), 1000));
+        let kahan = terms().kahan_sum();
+        assert!((kahan.v() - (1. + 1e-13)).abs() < 1e-15, "{}", kahan.v());
+        assert_eq!(kahan.d()[0], 1.);
+        assert!((kahan.d()[1] - 1e-13).abs() < 1e-25, "{:?}", kahan.d());
+    }
+}
@@ -5,6 +5,7 @@ pub mod cubic;
 pub mod deg;
 pub mod is_normal;
 pub mod is_zero;
+pub mod kahan;
 pub mod quadratic;
 pub mod quartic;
 pub mod recip;
@@ -0,0 +1,82 @@
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::r2::R2;
+
+/// One piece of a closed boundary, in SVG path terms (see [`crate::region::Region::boundary_path`]). Each piece starts where the previous one ended, and the first starts at the last one's `to`.
+#[derive(Clone, Debug, PartialEq, Tsify, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum PathSeg {
+    /// Straight line (SVG `L`)
+    Line { to: R2<f64> },
+    /// Elliptical arc (SVG `A`): radii, rotation of the x-axis (in degrees), and SVG's `large-arc-flag` and `sweep-flag` (`true` means the arc runs in the direction of increasing angle, i.e. counter-clockwise with the y-axis pointing up)
+    #[serde(rename_all = "camelCase")]
+    Arc { to: R2<f64>, rx: f64, ry: f64, rotation: f64, large_arc: bool, sweep: bool },
+}
+
+impl PathSeg {
+    pub fn to(&self) -> R2<f64> {
+        match self {
+            PathSeg::Line { to } => *to,
+            PathSeg::Arc { to, .. } => *to,
+        }
+    }
+
+    /// SVG path `d` attribute drawing the closed `path`.
+    pub fn svg(path: &[PathSeg]) -> String {
+        let Some(last) = path.last() else { return String::new() };
+        let start = last.to();
+        let mut d = format!("M {} {}", start.x, start.y);
+        for seg in path {
+            match seg {
+                PathSeg::Line { to } => d += &format!(" L {} {}", to.x, to.y),
+                PathSeg::Arc { to, rx, ry, rotation, large_arc, sweep } => d += &format!(
+                    " A {} {} {} {} {} {} {}",
+                    rx, ry, rotation, *large_arc as u8, *sweep as u8, to.x, to.y,
+                ),
+            }
+        }
+        d + " Z"
+    }
+
+    /// `n` points along this piece, starting from `from` (exclusive) and ending at `to` (inclusive); lines only yield `to`. Arcs are sampled evenly in their parametric angle, with their centers recovered from their endpoints as in SVG's "endpoint to center" conversion.
+    pub fn sample(&self, from: R2<f64>, n: usize) -> Vec<R2<f64>> {
+        match self {
+            PathSeg::Line { to } => vec![ *to ],
+            PathSeg::Arc { to, rx, ry, rotation, large_arc, sweep } => {
+                let (sin, cos) = rotation.to_radians().sin_cos();
+                let dx = (from.x - to.x) / 2.;
+                let dy = (from.y - to.y) / 2.;
+                let x1 = cos * dx + sin * dy;
+                let y1 = -sin * dx + cos * dy;
+                // Scale up radii that are too small to span the endpoints
+                let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+                let (rx, ry) = if lambda > 1. { (rx * lambda.sqrt(), ry * lambda.sqrt()) } else { (*rx, *ry) };
+                let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+                let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+                let sign = if large_arc == sweep { -1. } else { 1. };
+                let coef = sign * (num / den).max(0.).sqrt();
+                let cx1 = coef * rx * y1 / ry;
+                let cy1 = -coef * ry * x1 / rx;
+                let cx = cos * cx1 - sin * cy1 + (from.x + to.x) / 2.;
+                let cy = sin * cx1 + cos * cy1 + (from.y + to.y) / 2.;
+                let theta0 = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+                let theta1 = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+                let mut dtheta = (theta1 - theta0).rem_euclid(TAU);
+                if !sweep && dtheta > 0. {
+                    dtheta -= TAU;
+                }
+                (1..=n).map(|i| {
+                    if i == n {
+                        return *to
+                    }
+                    let theta = theta0 + dtheta * (i as f64) / (n as f64);
+                    let (s, c) = theta.sin_cos();
+                    R2 { x: cx + rx * cos * c - ry * sin * s, y: cy + rx * sin * c + ry * cos * s }
+                }).collect()
+            }
+        }
+    }
+}
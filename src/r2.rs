@@ -5,9 +5,10 @@ use derive_more::{Neg, From};
 use serde::{Deserialize, Serialize};
 use tsify::Tsify;
 
-use crate::{dual::Dual, rotate::{self, Rotate as _Rotate, RotateArg}, transform::{Transform::{self, Rotate, Translate, Scale, ScaleXY}, CanTransform}, sqrt::Sqrt, trig::Trig, to::To, math::recip::Recip};
+use crate::{dual::Dual, rotate::{self, Rotate as _Rotate, RotateArg}, transform::{Transform::{self, Reflect, Rotate, Translate, Scale, ScaleXY}, CanTransform}, sqrt::Sqrt, trig::Trig, to::To, math::recip::Recip};
 
 #[derive(Debug, Copy, Clone, From, Neg, PartialEq, Tsify, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct R2<D> {
     pub x: D,
     pub y: D,
@@ -38,6 +39,22 @@ impl<D: RotateArg> rotate::Rotate<D> for R2<D> {
     }
 }
 
+impl<D: RotateArg> R2<D> {
+    /// Mirror across the x-axis.
+    pub fn flip_y(&self) -> R2<D> {
+        R2 { x: self.x.clone(), y: -self.y.clone() }
+    }
+    /// Reflecting across the line through the origin in direction `self` is equivalent to [`Self::flip_y`] followed by rotating by this angle.
+    pub fn reflection_angle(&self) -> D {
+        let angle = self.y.atan2(&self.x);
+        angle.clone() + angle
+    }
+    /// Reflect across the line through the origin in direction `axis`.
+    pub fn reflect(&self, axis: &R2<D>) -> R2<D> {
+        self.flip_y().rotate(&axis.reflection_angle())
+    }
+}
+
 impl<
     D
     : Clone
@@ -52,6 +69,7 @@ impl<
             Scale(v) => self.clone() * v,
             ScaleXY(v) => self.clone() * v,
             Rotate(a) => self.rotate(&a),
+            Reflect(v) => self.reflect(v),
         }
     }
 }
@@ -3,6 +3,7 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     fmt::{self, Display, Formatter},
     iter::Sum,
+    f64::consts::PI,
     ops::{Add, Sub},
     rc::Rc,
 };
@@ -15,6 +16,7 @@ use crate::{
     dual::Dual,
     edge::{Edge, EdgeArg, E},
     math::abs::{Abs, AbsArg},
+    path::PathSeg,
     r2::R2,
     segment::Segment,
     shape::Shape,
@@ -112,6 +114,37 @@ where
     }
 }
 
+impl<D: Clone + Into<f64>> Region<D>
+where
+    R2<D>: To<R2<f64>>,
+    Shape<f64>: From<Shape<D>>,
+{
+    /// This region's outer boundary, as SVG path pieces: an elliptical arc per segment, or two for a segment spanning a whole shape (SVG can't draw an arc whose endpoints coincide). Encloses [`Self::total_area`]; holes left by child components aren't included.
+    pub fn boundary_path(&self) -> Vec<PathSeg> {
+        self.segments.iter().flat_map(|s| {
+            let edge = s.edge.borrow();
+            let shape: Shape<f64> = edge.set.borrow().shape.clone().into();
+            let (rx, ry, rotation) = match &shape {
+                Shape::Circle(c) => (c.r, c.r, 0.),
+                Shape::XYRR(e) => (e.r.x, e.r.y, 0.),
+                Shape::XYRRT(e) => (e.r.x, e.r.y, e.t.to_degrees()),
+            };
+            let theta0: f64 = edge.theta0.clone().into();
+            let theta1: f64 = edge.theta1.clone().into();
+            let theta = theta1 - theta0;
+            let (start, end) = if s.fwd { (&edge.node0, &edge.node1) } else { (&edge.node1, &edge.node0) };
+            let start: R2<f64> = start.borrow().p.clone().to();
+            let end: R2<f64> = end.borrow().p.clone().to();
+            let arc = |to: R2<f64>, theta: f64| PathSeg::Arc { to, rx: rx.abs(), ry: ry.abs(), rotation, large_arc: theta > PI, sweep: s.fwd };
+            if start == end {
+                vec![ arc(shape.arc_midpoint(theta0, theta1), theta / 2.), arc(end, theta / 2.) ]
+            } else {
+                vec![ arc(end, theta) ]
+            }
+        }).collect()
+    }
+}
+
 impl<D: fmt::Debug> Region<D> {
     pub fn segments_for_set(&self, set_idx: usize) -> Vec<&Segment<D>> {
         self.segments
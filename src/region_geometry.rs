@@ -0,0 +1,59 @@
+use crate::{r2::R2, region::Region, shape::Shape::{Circle, XYRR, XYRRT}};
+
+/// One boundary segment of a [`RegionGeometry`]: its endpoints, and the parameters of the secant (between the chord and the shape's boundary).
+#[derive(Clone, Debug)]
+pub struct SegmentGeometry {
+    pub start: R2<f64>,
+    pub end: R2<f64>,
+    /// Product of the bounding shape's radii (`r²` for circles, `rx·ry` for ellipses)
+    pub r2: f64,
+    /// Angle span of the underlying edge, in terms of the bounding shape
+    pub theta: f64,
+    pub fwd: bool,
+}
+
+/// Plain-data copy of a [`Region`]'s boundary (no `Rc`s), so that areas can be computed off the main thread.
+#[derive(Clone, Debug)]
+pub struct RegionGeometry {
+    pub key: String,
+    pub segments: Vec<SegmentGeometry>,
+}
+
+impl From<&Region<f64>> for RegionGeometry {
+    fn from(region: &Region<f64>) -> Self {
+        let segments = region.segments.iter().map(|s| {
+            let edge = s.edge.borrow();
+            let r2 = match &edge.set.borrow().shape {
+                Circle(c) => c.r * c.r,
+                XYRR(e) => e.r.x * e.r.y,
+                XYRRT(e) => e.r.x * e.r.y,
+            };
+            SegmentGeometry {
+                start: s.start().borrow().p,
+                end: s.end().borrow().p,
+                r2,
+                theta: edge.theta(),
+                fwd: s.fwd,
+            }
+        }).collect();
+        RegionGeometry { key: region.key.clone(), segments }
+    }
+}
+
+impl RegionGeometry {
+    /// Mirrors [`Region::polygon_area`], operation for operation.
+    pub fn polygon_area(&self) -> f64 {
+        self.segments.iter().map(|s| s.start.x * s.end.y - s.start.y * s.end.x).sum::<f64>() / 2.
+    }
+    /// Mirrors [`Region::secant_area`], operation for operation.
+    pub fn secant_area(&self) -> f64 {
+        self.segments.iter().map(|s| {
+            let secant_area = s.r2 / 2. * (s.theta - s.theta.sin());
+            if s.fwd { secant_area } else { -secant_area }
+        }).sum::<f64>()
+    }
+    /// Area of this region (including any child components); identical to the [`Region::total_area`] it was copied from.
+    pub fn total_area(&self) -> f64 {
+        (self.polygon_area() + self.secant_area()).abs()
+    }
+}
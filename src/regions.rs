@@ -3,7 +3,7 @@ use std::collections::BTreeSet;
 use serde::{Serialize, Deserialize};
 use tsify::Tsify;
 
-use crate::{dual::Dual, r2::R2, component, set::Set, region, segment};
+use crate::{dual::Dual, r2::R2, component, path::PathSeg, set::Set, region, segment};
 
 #[derive(Clone, Debug, Tsify, Serialize, Deserialize)]
 pub struct Point {
@@ -44,6 +44,9 @@ pub struct Region {
     pub area: f64,
     pub container_set_idxs: Vec<usize>,
     pub child_component_keys: Vec<String>,
+    /// Outer boundary, as SVG path pieces (see [`region::Region::boundary_path`])
+    #[serde(default)]
+    pub path: Vec<PathSeg>,
 }
 
 impl From<&region::Region<Dual>> for Region {
@@ -54,6 +57,7 @@ impl From<&region::Region<Dual>> for Region {
             area: region.area().v(),
             container_set_idxs: region.container_set_idxs.clone().into_iter().collect(),
             child_component_keys: region.child_components.iter().map(|c| c.borrow().key.0.clone()).collect(),
+            path: region.boundary_path(),
         }
     }
 }
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+/// Configuration for [`crate::model::Model::train_rmsprop`].
+#[derive(Debug, Clone, Copy, PartialEq, Tsify, Serialize, Deserialize)]
+pub struct RmsPropConfig {
+    /// Decay rate of the running mean of squared gradients
+    pub decay: f64,
+    /// Added to the RMS before dividing, to avoid blowing up coordinates with vanishing gradients
+    pub epsilon: f64,
+}
+
+impl Default for RmsPropConfig {
+    fn default() -> Self {
+        RmsPropConfig { decay: 0.9, epsilon: 1e-8 }
+    }
+}
+
+/// Per-coordinate running mean of squared gradients.
+#[derive(Debug, Clone)]
+pub struct RmsProp {
+    pub config: RmsPropConfig,
+    pub mean_sq: Vec<f64>,
+}
+
+impl RmsProp {
+    pub fn new(config: RmsPropConfig, n: usize) -> RmsProp {
+        RmsProp { config, mean_sq: vec![0.; n] }
+    }
+
+    /// Fold `grad` into the running mean, and return the step vector `-lr * grad / (√mean_sq + ε)`.
+    pub fn step_vec(&mut self, grad: &[f64], lr: f64) -> Vec<f64> {
+        let RmsPropConfig { decay, epsilon } = self.config;
+        self.mean_sq.iter_mut().zip(grad.iter()).map(|(m, g)| {
+            *m = decay * *m + (1. - decay) * g * g;
+            -lr * g / (m.sqrt() + epsilon)
+        }).collect()
+    }
+}
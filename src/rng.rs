@@ -0,0 +1,51 @@
+use std::f64::consts::PI;
+
+/// Small, seedable PRNG (SplitMix64), so that randomized training (e.g. [`crate::model::Model::train_annealed`]) is reproducible without pulling in an RNG crate.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal (Box-Muller)
+    pub fn normal(&mut self) -> f64 {
+        let u1 = 1. - self.uniform();
+        let u2 = self.uniform();
+        (-2. * u1.ln()).sqrt() * (2. * PI * u2).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn normal_moments() {
+        let mut rng = Rng::new(123);
+        let n = 10000;
+        let xs: Vec<f64> = (0..n).map(|_| rng.normal()).collect();
+        let mean = xs.iter().sum::<f64>() / n as f64;
+        let var = xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n as f64;
+        assert!(mean.abs() < 0.05, "{}", mean);
+        assert!((var - 1.).abs() < 0.05, "{}", var);
+        assert_eq!(Rng::new(123).next_u64(), Rng::new(123).next_u64());
+    }
+}
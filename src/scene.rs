@@ -1,10 +1,11 @@
 use core::f64;
-use std::{cell::RefCell, rc::Rc, collections::{BTreeSet, BTreeMap}, ops::{Neg, Add, Sub, Mul, Div}};
+use std::{cell::RefCell, rc::Rc, collections::{BTreeSet, BTreeMap}, f64::consts::{PI, TAU}, ops::{Neg, Add, Sub, Mul, Div}};
 
+use itertools::Itertools;
 use log::{debug, info, error};
 use ordered_float::OrderedFloat;
 
-use crate::{node::{N, Node}, contains::{Contains, ShapeContainsPoint}, distance::Distance, region::RegionArg, set::S, shape::{Shape, AreaArg}, theta_points::ThetaPoints, intersect::{Intersect, IntersectShapesArg}, r2::R2, transform::{CanTransform, HasProjection, CanProject}, dual::Dual, to::To, math::deg::Deg, fmt::Fmt, component::{Component, self}, set::Set};
+use crate::{node::{N, Node}, contains::{Contains, ShapeContainsPoint}, distance::Distance, edge::{E, Edge}, region::{Region, RegionArg}, region_geometry::RegionGeometry, segment::Segment, set::S, shape::{Shape, AreaArg}, theta_points::ThetaPoints, intersect::{IntersectShapesArg, Intersections}, r2::R2, transform::{CanTransform, HasProjection, CanProject, Projection, Transform}, ellipses::xyrrt::XYRRT, dual::Dual, to::To, math::{deg::Deg, kahan::KahanSum}, fmt::Fmt, component::{Component, C, self}, set::Set, hull::{self, Hull}};
 
 /// Collection of [`Shape`]s (wrapped in [`Set`]s), and segmented into connected [`Component`]s.
 #[derive(Clone, Debug)]
@@ -63,6 +64,8 @@ where
         let zero = set_ptrs[0].borrow().zero();
 
         let mut is_directly_connected: Vec<Vec<bool>> = Vec::new();
+        // Pairs (idx, jdx), idx < jdx, of shapes with identical boundaries
+        let mut coincident: BTreeSet<(usize, usize)> = BTreeSet::new();
         // Intersect all shapes, pair-wise
         for (idx, set_ptr) in set_ptrs.iter().enumerate() {
             let mut directly_connected: Vec<bool> = Vec::new();
@@ -75,19 +78,16 @@ where
             directly_connected.push(true);
             for jdx in (idx + 1)..num_shapes {
                 let shape1 = set_ptrs[jdx].borrow().shape.clone();
-                let mut intersections = shape0.intersect(&shape1);
-                let mut i = 0;
-                loop {
-                    if i >= intersections.len() { break }
-                    let cur = &intersections[i];
-                    if i + 1 < intersections.len() && cur.distance(&intersections[i + 1]).into() < merge_threshold {
-                        info!("Skipping apparent tangent point: {} == {}", cur, intersections[i + 1]);
-                        intersections.remove(i);
-                        intersections.remove(i);
-                    } else {
-                        i += 1;
-                    }
-                }
+                let mut intersections = match shape0.intersections(&shape1) {
+                    Intersections::Points(points) => points,
+                    Intersections::Coincident => {
+                        // Treated as fully-overlapping: the lower-indexed shape contains the other (see `unconnected_containers` below)
+                        info!("Shapes {} and {} coincide", idx, jdx);
+                        coincident.insert((idx, jdx));
+                        vec![]
+                    },
+                };
+                Scene::remove_tangent_points(&mut intersections, merge_threshold);
                 if intersections.is_empty() {
                     directly_connected.push(false);
                 } else {
@@ -168,6 +168,12 @@ where
             let shape = &set_ptr.borrow().shape;
             let mut containers: BTreeSet<usize> = BTreeSet::new();
             for (jdx, container) in set_ptrs.iter().enumerate() {
+                if coincident.contains(&(idx.min(jdx), idx.max(jdx))) {
+                    if jdx < idx {
+                        containers.insert(jdx);
+                    }
+                    continue;
+                }
                 if !is_connected[idx][jdx] &&
                     // This check can false-positive if the shapes are tangent at theta == 0, so we check center-containment below as well
                     container.borrow().shape.contains(&shape.point(zero.clone())) &&
@@ -266,6 +272,222 @@ where
         Scene { sets: set_ptrs, components, }
     }
 
+    /// Remove pairs of near-identical points (doubled roots, i.e. tangent points) from `intersections`.
+    fn remove_tangent_points(intersections: &mut Vec<R2<D>>, merge_threshold: f64) {
+        let mut i = 0;
+        loop {
+            if i >= intersections.len() { break }
+            let cur = &intersections[i];
+            // Doubled roots (tangent points) needn't be adjacent, e.g. for concentric shapes
+            match ((i + 1)..intersections.len()).find(|j| cur.distance(&intersections[*j]).into() < merge_threshold) {
+                Some(j) => {
+                    info!("Skipping apparent tangent point: {} == {}", cur, intersections[j]);
+                    intersections.remove(j);
+                    intersections.remove(i);
+                },
+                None => {
+                    i += 1;
+                },
+            }
+        }
+    }
+
+    /// [`Scene`] for `shapes`, reusing this scene's topology (components, edges, regions, and their keys) and only recomputing geometry (intersection points, edge angles, areas). Consecutive training steps usually have the same topology, and this skips the region-graph traversal in [`Scene::new`].
+    ///
+    /// Returns `None` (and [`Scene::new`] should be used instead) if the topology differs: a pair of shapes intersects at a different number of points, intersections appear in a different order along a shape, an edge is contained by different shapes, or a component moves to a different container region. Also returns `None` for topologies it doesn't attempt to match (coincident shapes, or nodes where more than two shapes intersect).
+    pub fn relayout(&self, shapes: Vec<Shape<D>>) -> Option<Scene<D>> {
+        let num_shapes = shapes.len();
+        if num_shapes != self.sets.len() {
+            return None;
+        }
+        let merge_threshold = 1e-7;
+        let zero = shapes[0].zero();
+        let set_ptrs: Vec<S<D>> = self.sets.iter().zip(shapes).map(|(set, shape)| {
+            let set = set.borrow();
+            Rc::new(RefCell::new(Set { idx: set.idx, child_component_keys: set.child_component_keys.clone(), shape }))
+        }).collect();
+
+        // Existing nodes, by the pair of shapes that intersect there
+        let mut old_nodes: BTreeMap<(usize, usize), Vec<N<D>>> = BTreeMap::new();
+        for component in &self.components {
+            if component.set_idxs.len() == 1 {
+                continue;
+            }
+            for node in &component.nodes {
+                let idxs: Vec<usize> = node.borrow().shape_thetas.keys().cloned().collect();
+                if node.borrow().n != 1 || idxs.len() != 2 {
+                    debug!("relayout: node {} is shared by {} shapes", node.borrow().idx, idxs.len());
+                    return None;
+                }
+                old_nodes.entry((idxs[0], idxs[1])).or_default().push(node.clone());
+            }
+        }
+
+        // Intersect all shapes pair-wise, and match intersections to existing nodes
+        let mut new_nodes: BTreeMap<*const RefCell<Node<D>>, N<D>> = BTreeMap::new();
+        let mut all_points: Vec<R2<f64>> = Vec::new();
+        for idx in 0..num_shapes {
+            let shape0 = set_ptrs[idx].borrow().shape.clone();
+            for (jdx, set1) in set_ptrs.iter().enumerate().skip(idx + 1) {
+                let shape1 = set1.borrow().shape.clone();
+                let mut intersections = match shape0.intersections(&shape1) {
+                    Intersections::Points(points) => points,
+                    Intersections::Coincident => return None,
+                };
+                Scene::remove_tangent_points(&mut intersections, merge_threshold);
+                let empty = vec![];
+                let olds = old_nodes.get(&(idx, jdx)).unwrap_or(&empty);
+                if olds.len() != intersections.len() {
+                    debug!("relayout: shapes {} and {} intersect at {} points, previously {}", idx, jdx, intersections.len(), olds.len());
+                    return None;
+                }
+                for p in intersections {
+                    let pf: R2<f64> = p.clone().into();
+                    if all_points.iter().any(|q| q.distance(&pf) < merge_threshold) {
+                        // `Scene::new` would merge this intersection with another pair's
+                        return None;
+                    }
+                    all_points.push(pf);
+                    let (old, _) = olds.iter().map(|old| {
+                        let q: R2<f64> = old.borrow().p.clone().into();
+                        (old, q.distance(&pf))
+                    }).min_by(|(_, d0), (_, d1)| d0.total_cmp(d1)).unwrap();
+                    let key = Rc::as_ptr(old);
+                    if new_nodes.contains_key(&key) {
+                        debug!("relayout: ambiguous match for node {}", old.borrow().idx);
+                        return None;
+                    }
+                    let theta0 = shape0.theta(&p);
+                    let theta1 = shape1.theta(&p);
+                    let node = Node {
+                        idx: old.borrow().idx,
+                        p,
+                        n: 1,
+                        shape_thetas: vec![(idx, theta0), (jdx, theta1)].into_iter().collect(),
+                        edges: Vec::new(),
+                    };
+                    new_nodes.insert(key, Rc::new(RefCell::new(node)));
+                }
+            }
+        }
+
+        // Containers of each shape (among shapes it isn't connected to); must match each component's existing containers
+        let mut unconnected_containers: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); num_shapes];
+        for component in &self.components {
+            for idx in &component.set_idxs {
+                let shape = set_ptrs[*idx].borrow().shape.clone();
+                let containers: BTreeSet<usize> = set_ptrs.iter().enumerate().filter(|(jdx, container)| {
+                    !component.set_idxs.contains(jdx) &&
+                    container.borrow().shape.contains(&shape.point(zero.clone())) &&
+                    container.borrow().shape.contains(&shape.center())
+                }).map(|(jdx, _)| jdx).collect();
+                if containers != component.container_set_idxs {
+                    debug!("relayout: shape {} containers {:?} != {:?}", idx, containers, component.container_set_idxs);
+                    return None;
+                }
+                unconnected_containers[*idx] = containers;
+            }
+        }
+
+        let mut components: Vec<Component<D>> = Vec::new();
+        for component in &self.components {
+            if component.set_idxs.len() == 1 {
+                components.push(Component::new(component.set_idxs.clone(), &unconnected_containers, &vec![vec![]; num_shapes], &set_ptrs, num_shapes));
+                continue;
+            }
+            let nodes: Vec<N<D>> = component.nodes.iter().map(|n| new_nodes[&Rc::as_ptr(n)].clone()).collect();
+            let mut new_edges: BTreeMap<*const RefCell<Edge<D>>, E<D>> = BTreeMap::new();
+            let mut set_spans: BTreeMap<usize, f64> = BTreeMap::new();
+            let mut edges: Vec<E<D>> = Vec::new();
+            for old_edge in &component.edges {
+                let edge = old_edge.borrow();
+                let set_idx = edge.set_idx();
+                let set = set_ptrs[set_idx].clone();
+                let node0 = new_nodes[&Rc::as_ptr(&edge.node0)].clone();
+                let node1 = new_nodes[&Rc::as_ptr(&edge.node1)].clone();
+                let theta0 = node0.borrow().theta(set_idx);
+                let theta1 = node1.borrow().theta(set_idx);
+                let theta1 = if theta1 <= theta0 { theta1 + TAU } else { theta1 };
+                let span: f64 = (theta1.clone() - theta0.clone()).into();
+                *set_spans.entry(set_idx).or_default() += span;
+                // Edges must be contained by the same shapes as before
+                let arc_midpoint = set.borrow().shape.arc_midpoint(theta0.clone(), theta1.clone());
+                let mut container_set_idxs = component.container_set_idxs.clone();
+                for cdx in &component.set_idxs {
+                    if *cdx != set_idx && set_ptrs[*cdx].borrow().shape.contains(&arc_midpoint) {
+                        container_set_idxs.insert(*cdx);
+                    }
+                }
+                if container_set_idxs != edge.container_set_idxs {
+                    debug!("relayout: edge {} containers {:?} != {:?}", edge.idx, container_set_idxs, edge.container_set_idxs);
+                    return None;
+                }
+                let new_edge = Rc::new(RefCell::new(Edge {
+                    idx: edge.idx,
+                    set,
+                    node0: node0.clone(),
+                    node1: node1.clone(),
+                    theta0,
+                    theta1,
+                    container_set_idxs,
+                    is_component_boundary: edge.is_component_boundary,
+                    visits: edge.visits,
+                }));
+                node0.borrow_mut().add_edge(new_edge.clone());
+                node1.borrow_mut().add_edge(new_edge.clone());
+                new_edges.insert(Rc::as_ptr(old_edge), new_edge.clone());
+                edges.push(new_edge);
+            }
+            // Each shape's edges span one full revolution iff its intersections are in the same (cyclic) order as before
+            if set_spans.values().any(|span| (span - TAU).abs() > 1e-9) {
+                debug!("relayout: intersections reordered along shapes: {:?}", set_spans);
+                return None;
+            }
+            let segment = |s: &Segment<D>| Segment { edge: new_edges[&Rc::as_ptr(&s.edge)].clone(), fwd: s.fwd };
+            let regions = component.regions.iter().map(|region| {
+                Region::new(region.key.clone(), region.segments.iter().map(segment).collect(), region.container_set_idxs.clone())
+            }).collect();
+            let hull = Hull(component.hull.iter().map(segment).collect());
+            let sets = component.set_idxs.iter().map(|idx| set_ptrs[*idx].clone()).collect();
+            components.push(Component {
+                key: component.key.clone(),
+                set_idxs: component.set_idxs.clone(),
+                sets,
+                nodes,
+                edges,
+                container_set_idxs: component.container_set_idxs.clone(),
+                regions,
+                child_component_keys: BTreeSet::new(),
+                hull,
+            });
+        }
+
+        // Re-link child components to the same container regions
+        let component_ptrs: BTreeMap<component::Key, C<D>> = components.iter().map(|c| (c.key.clone(), Rc::new(RefCell::new(c.clone())))).collect();
+        for (component, old) in components.iter_mut().zip(self.components.iter()) {
+            component.child_component_keys = old.child_component_keys.clone();
+            let container_shapes: BTreeMap<usize, Shape<f64>> = component.sets.iter().map(|s| (s.borrow().idx, s.borrow().shape.clone().into())).collect();
+            for (region, old_region) in component.regions.iter_mut().zip(old.regions.iter()) {
+                for child in &old_region.child_components {
+                    let key = child.borrow().key.clone();
+                    let p: R2<f64> = set_ptrs[child.borrow().set_idxs[0]].borrow().shape.c().into();
+                    if !region.contains(&p, &container_shapes) {
+                        debug!("relayout: component {} left region {}", key, region.key);
+                        return None;
+                    }
+                    region.child_components.push(component_ptrs[&key].clone());
+                }
+            }
+        }
+        for component in &components {
+            if let Err(err) = component.verify_areas(0.001) {
+                debug!("relayout: component {} failed area verification: {}", component.key, err);
+                return None;
+            }
+        }
+        Some(Scene { sets: set_ptrs, components })
+    }
+
     pub fn compute_component_depths(components: &mut Vec<Component<D>>) -> BTreeMap<component::Key, i64> {
         let components_map = components.iter().map(|c| (c.key.clone(), c.clone())).collect::<BTreeMap<_, _>>();
         let mut component_depths_map = BTreeMap::new();
@@ -300,41 +522,138 @@ where
     }
 
     pub fn total_area(&self) -> D {
-        self.components.iter().filter(|c| c.container_set_idxs.is_empty()).map(|c| c.area()).sum()
+        self.components.iter().filter(|c| c.container_set_idxs.is_empty()).map(|c| c.area()).kahan_sum()
     }
 
     pub fn area(&self, key: &String) -> Option<D> {
+        self.area_with(key, false)
+    }
+
+    /// Like [`Self::area`], but each exclusive region's area in `-ε..0` (where `ε` is [`Self::negative_area_epsilon`]; floating-point noise, e.g. from near-tangent intersections) is treated as 0. More-negative areas are returned as-is (see [`Self::negative_region_report`]).
+    pub fn area_clamped(&self, key: &String) -> Option<D> {
+        self.area_with(key, true)
+    }
+
+    fn area_with(&self, key: &String, clamp: bool) -> Option<D> {
         match key.split_once('*') {
             Some((prefix, suffix)) => {
                 let k0 = format!("{}-{}", prefix, suffix);
                 let k1 = format!("{}{}{}", prefix, prefix.len(), suffix);
                 if k0.chars().all(|ch| ch == '-') {
-                    return self.area(&k1)
+                    return self.area_with(&k1, clamp)
                 } else {
-                    let a0 = self.area(&k0).unwrap_or_else(|| self.zero());
-                    let a1 = self.area(&k1).unwrap_or_else(|| self.zero());
+                    let a0 = self.area_with(&k0, clamp).unwrap_or_else(|| self.zero());
+                    let a1 = self.area_with(&k1, clamp).unwrap_or_else(|| self.zero());
                     Some(a0 + a1)
                 }
             }
             None => {
-                let areas =
-                    self
+                let area = self
                     .components
                     .iter()
                     .flat_map(|c| c.regions.iter())
                     .filter(|r| &r.key == key)
-                    .map(|r| r.area());
-                // TODO: SumOpt trait?
-                let mut sum = None;
-                for area in areas {
-                    match sum {
-                        None => sum = Some(area),
-                        Some(s) => sum = Some(s + area),
+                    .map(|r| r.area())
+                    .kahan_sum_opt()?;
+                if clamp {
+                    let v: f64 = area.clone().into();
+                    if v < 0. && v > -self.negative_area_epsilon() {
+                        debug!("Clamping region {} area {} to 0", key, v);
+                        return Some(self.zero());
+                    }
+                }
+                Some(area)
+            }
+        }
+    }
+
+    /// Magnitude below which negative region areas are considered floating-point noise: [`NEGATIVE_AREA_REL_EPSILON`] times [`Self::total_area`], so that it scales with the diagram.
+    pub fn negative_area_epsilon(&self) -> f64 {
+        let epsilon: f64 = (self.total_area() * NEGATIVE_AREA_REL_EPSILON).into();
+        epsilon.abs()
+    }
+
+    /// Exclusive regions whose area is below `-ε` (see [`Self::negative_area_epsilon`]): too negative to be floating-point noise, these indicate a geometry bug.
+    pub fn negative_region_report(&self) -> Vec<(String, f64)> {
+        let epsilon = self.negative_area_epsilon();
+        self.area_matrix().into_iter().filter_map(|(key, area)| {
+            let area: f64 = area.into();
+            if area < -epsilon {
+                Some((key, area))
+            } else {
+                None
+            }
+        }).collect()
+    }
+
+    /// Number of connected components (groups of shapes linked by boundary intersections). Components may be disjoint, or nested inside a region of another component.
+    pub fn component_count(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Index (into [`Self::components`]) of the component containing set `set_idx`.
+    pub fn component_of(&self, set_idx: usize) -> usize {
+        self.components.iter().position(|c| c.set_idxs.contains(&set_idx)).unwrap_or_else(|| panic!("Set {} not found in any of {} components", set_idx, self.components.len()))
+    }
+
+    /// Pairs `(a, b)` where set `a` lies entirely inside set `b`, sorted. Sets whose boundaries intersect are never in such a pair; this reads the containers computed (via [`Contains`]) during scene construction.
+    pub fn containment(&self) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<(usize, usize)> = self.components.iter().flat_map(|c| {
+            c.set_idxs.iter().flat_map(|a| c.container_set_idxs.iter().map(move |b| (*a, *b)))
+        }).collect();
+        pairs.sort();
+        pairs
+    }
+
+    /// Pairs of region keys whose regions share at least one boundary edge, each pair listed once (with the lesser key first), sorted. Regions of a nested component are adjacent to the containing region (of the parent component) along the nested component's outer boundary.
+    pub fn region_adjacency(&self) -> Vec<(String, String)> {
+        let mut pairs: BTreeSet<(String, String)> = BTreeSet::new();
+        let mut insert = |a: &String, b: &String| {
+            if a < b {
+                pairs.insert((a.clone(), b.clone()));
+            } else if b < a {
+                pairs.insert((b.clone(), a.clone()));
+            }
+        };
+        for component in &self.components {
+            let mut edge_keys: BTreeMap<usize, Vec<&String>> = BTreeMap::new();
+            for region in &component.regions {
+                for segment in &region.segments {
+                    edge_keys.entry(segment.edge.borrow().idx).or_default().push(&region.key);
+                }
+                for child in &region.child_components {
+                    for child_region in &child.borrow().regions {
+                        if child_region.segments.iter().any(|s| s.edge.borrow().is_component_boundary) {
+                            insert(&region.key, &child_region.key);
+                        }
                     }
                 }
-                sum
+            }
+            for keys in edge_keys.values() {
+                for (a, b) in keys.iter().tuple_combinations() {
+                    insert(a, b);
+                }
             }
         }
+        pairs.into_iter().collect()
+    }
+
+    /// Exclusive keys (e.g. `"01-"`) of the regions present in this scene, sorted; combinations of sets that don't overlap (or that are split by a containing set) are absent.
+    pub fn region_keys(&self) -> Vec<String> {
+        self.components.iter().flat_map(|c| c.regions.iter().map(|r| r.key.clone())).collect::<BTreeSet<_>>().into_iter().collect()
+    }
+
+    /// Area of every non-empty region, keyed by exclusive region key (e.g. `"01-"`), summing same-keyed regions across components.
+    pub fn area_matrix(&self) -> BTreeMap<String, D> {
+        let mut areas: BTreeMap<String, D> = BTreeMap::new();
+        for region in self.components.iter().flat_map(|c| c.regions.iter()) {
+            let area = region.area();
+            match areas.remove(&region.key) {
+                Some(prv) => areas.insert(region.key.clone(), prv + area),
+                None => areas.insert(region.key.clone(), area),
+            };
+        }
+        areas
     }
 
     pub fn len(&self) -> usize {
@@ -344,6 +663,133 @@ where
     pub fn zero(&self) -> D {
         self.sets[0].borrow().zero()
     }
+
+    /// Convex hull of all shapes' boundaries (each sampled at [`HULL_SAMPLES`] evenly-spaced angles), in counter-clockwise order starting from the lowest point.
+    pub fn convex_hull(&self) -> Vec<R2<f64>> {
+        let points: Vec<R2<f64>> = self.sets.iter().flat_map(|set| {
+            let shape: Shape<f64> = set.borrow().shape.clone().into();
+            boundary_points(&shape, HULL_SAMPLES)
+        }).collect();
+        hull::convex_hull(&points)
+    }
+}
+
+impl Scene<f64> {
+    /// Exclusive key (e.g. `"0-2"`) of the region containing `p`, or `None` if `p` lies outside all shapes. Membership in each set is tested directly (via [`Contains`]), so points in "holes" (e.g. inside a nested shape, but outside its container's other regions) get the right key.
+    pub fn region_at(&self, p: R2<f64>) -> Option<String> {
+        let mut any = false;
+        let key = self.sets.iter().enumerate().map(|(idx, set)| {
+            if set.borrow().shape.contains(&p) {
+                any = true;
+                idx.to_string()
+            } else {
+                "-".to_string()
+            }
+        }).collect::<String>();
+        if any { Some(key) } else { None }
+    }
+
+    /// Translation, rotation, and scale mapping this scene to a canonical pose: the (area-weighted) centroid of its shapes at the origin, principal axis of their second moments horizontal (pointing toward the heavier tail of the third moment), and unit total area. Scenes that differ only by such transforms have the same [`Self::normalized`] scene, up to floating-point error, except when the axis or its direction is undefined: e.g. isotropic or point-symmetric layouts (which are left unrotated, and possibly reversed, respectively).
+    pub fn canonical_pose(&self) -> Projection<f64> {
+        // (area, center, radii, rotation) of each shape
+        let shapes: Vec<(f64, R2<f64>, R2<f64>, f64)> = self.sets.iter().map(|set| {
+            let shape = &set.borrow().shape;
+            let (r, t) = match shape {
+                Shape::Circle(c) => (R2 { x: c.r, y: c.r }, 0.),
+                Shape::XYRR(e) => (e.r, 0.),
+                Shape::XYRRT(e) => (e.r, e.t),
+            };
+            (shape.area().abs(), shape.center(), r, t)
+        }).collect();
+        let mass: f64 = shapes.iter().map(|(a, ..)| a).sum();
+        let centroid = shapes.iter().fold(R2 { x: 0., y: 0. }, |sum, (a, c, ..)| sum + *c * *a) / mass;
+        // Second moment of an ellipse about its center, along the direction at angle `theta`
+        let moment = |a: f64, r: &R2<f64>, t: f64, theta: f64| a / 4. * (r.x * r.x * (t - theta).cos().powi(2) + r.y * r.y * (t - theta).sin().powi(2));
+        let (mut ixx, mut iyy, mut ixy) = (0., 0., 0.);
+        for (a, c, r, t) in &shapes {
+            let d = *c - centroid;
+            ixx += moment(*a, r, *t, 0.) + a * d.x * d.x;
+            iyy += moment(*a, r, *t, PI / 2.) + a * d.y * d.y;
+            ixy += a / 4. * (r.x * r.x - r.y * r.y) * t.sin() * t.cos() + a * d.x * d.y;
+        }
+        let mut theta = 0.5 * (2. * ixy).atan2(ixx - iyy);
+        // Third moment along the principal axis; shapes are symmetric about their centers, so each contributes `a·x³ + 3·x·Ixx`
+        let m3: f64 = shapes.iter().map(|(a, c, r, t)| {
+            let d = *c - centroid;
+            let x = d.x * theta.cos() + d.y * theta.sin();
+            a * x.powi(3) + 3. * x * moment(*a, r, *t, theta)
+        }).sum();
+        if m3 < 0. {
+            theta += PI;
+        }
+        Projection(vec![
+            Transform::Translate(-centroid),
+            Transform::Rotate(-theta),
+            Transform::Scale(1. / self.total_area().sqrt()),
+        ])
+    }
+
+    /// This scene, transformed into its [`Self::canonical_pose`]. Ellipses' rotations are reduced to `[0, π)`.
+    pub fn normalized(&self) -> Scene<f64> {
+        let projection = self.canonical_pose();
+        let shapes: Vec<Shape<f64>> = self.sets.iter().map(|set| {
+            match set.borrow().shape.apply(&projection) {
+                Shape::XYRRT(e) => Shape::XYRRT(XYRRT { t: e.t.rem_euclid(PI), ..e }),
+                shape => shape,
+            }
+        }).collect();
+        Scene::new(shapes)
+    }
+
+    /// Plain-data copies of each component's regions' boundaries (see [`RegionGeometry`]), indexed like `self.components[i].regions[j]`.
+    pub fn region_geometries(&self) -> Vec<Vec<RegionGeometry>> {
+        self.components.iter().map(|c| c.regions.iter().map(RegionGeometry::from).collect()).collect()
+    }
+
+    /// [`Self::area_matrix`], recomputed from each region's boundary geometry.
+    pub fn area_matrix_recomputed(&self) -> BTreeMap<String, f64> {
+        let total_areas = self.region_geometries().iter().map(|regions| regions.iter().map(RegionGeometry::total_area).collect()).collect();
+        self.area_matrix_from(total_areas)
+    }
+
+    /// [`Self::area_matrix_recomputed`], with each connected component's regions computed on a separate thread (components don't share any boundary, so they can be processed independently). Results are identical to the serial version.
+    #[cfg(feature = "parallel")]
+    pub fn area_matrix_par(&self) -> BTreeMap<String, f64> {
+        use rayon::prelude::*;
+        let total_areas = self.region_geometries().par_iter().map(|regions| regions.iter().map(RegionGeometry::total_area).collect()).collect();
+        self.area_matrix_from(total_areas)
+    }
+
+    /// Combine per-region total areas (indexed like `self.components[i].regions[j]`) into an [`Self::area_matrix`]: subtract each region's nested child components' areas, and sum same-keyed regions across components.
+    fn area_matrix_from(&self, total_areas: Vec<Vec<f64>>) -> BTreeMap<String, f64> {
+        let component_idxs: BTreeMap<&component::Key, usize> = self.components.iter().enumerate().map(|(idx, c)| (&c.key, idx)).collect();
+        let component_areas: Vec<f64> = total_areas.iter().map(|areas| areas.iter().cloned().kahan_sum()).collect();
+        let mut areas: BTreeMap<String, f64> = BTreeMap::new();
+        for (component, totals) in self.components.iter().zip(total_areas.iter()) {
+            for (region, total) in component.regions.iter().zip(totals.iter()) {
+                let mut area = *total;
+                for child_component in &region.child_components {
+                    area -= component_areas[component_idxs[&child_component.borrow().key]];
+                }
+                match areas.remove(&region.key) {
+                    Some(prv) => areas.insert(region.key.clone(), prv + area),
+                    None => areas.insert(region.key.clone(), area),
+                };
+            }
+        }
+        areas
+    }
+}
+
+/// Negative region areas smaller in magnitude than this fraction of the scene's total area are considered floating-point noise (see [`Scene::negative_area_epsilon`]).
+pub const NEGATIVE_AREA_REL_EPSILON: f64 = 1e-4;
+
+/// Number of boundary points sampled per shape, in [`Scene::convex_hull`].
+pub const HULL_SAMPLES: usize = 64;
+
+/// `n` points on `shape`'s boundary, at evenly-spaced angles (in the shape's unit-circle projection).
+pub fn boundary_points(shape: &Shape<f64>, n: usize) -> Vec<R2<f64>> {
+    (0..n).map(|idx| shape.point(2. * f64::consts::PI * (idx as f64) / (n as f64))).collect()
 }
 
 #[cfg(test)]
@@ -353,7 +799,7 @@ pub mod tests {
     use itertools::Itertools;
     use log::debug;
 
-    use crate::{math::{deg::Deg, round::round}, dual::Dual, fmt::Fmt, shape::{xyrr, circle, Shapes, xyrrt}, to::To, duals::D};
+    use crate::{math::{deg::Deg, round::round}, dual::Dual, fmt::Fmt, path::PathSeg, shape::{xyrr, circle, Shapes, xyrrt}, to::To, duals::D};
 
     use super::*;
     use test_log::test;
@@ -473,6 +919,78 @@ pub mod tests {
         mask.map(|i| ellipses[i].clone())
     }
 
+    #[test]
+    fn convex_hull() {
+        let scene = Scene::new(vec![ circle(0., 0., 1.), circle(1., 0., 1.) ]);
+        let hull = scene.convex_hull();
+        // Starts from the lowest (then leftmost) point: the bottom of circle 0
+        assert_relative_eq!(hull[0].x, 0., epsilon = 1e-12);
+        assert_relative_eq!(hull[0].y, -1., epsilon = 1e-12);
+        let n = hull.len();
+        for idx in 0..n {
+            let (a, b, c) = (hull[idx], hull[(idx + 1) % n], hull[(idx + 2) % n]);
+            let cross = (b.x - a.x) * (c.y - b.y) - (b.y - a.y) * (c.x - b.x);
+            assert!(cross > 0., "Not CCW at {}: {:?}", idx, hull);
+        }
+        assert!(hull.iter().all(|p| p.x >= -1. - 1e-12 && p.x <= 2. + 1e-12));
+
+        // Single shape: every sampled point is on the hull
+        let scene = Scene::new(vec![ circle(0., 0., 1.) ]);
+        assert_eq!(scene.convex_hull().len(), HULL_SAMPLES);
+    }
+
+    #[test]
+    fn components() {
+        let scene = Scene::new(vec![ circle(0., 0., 1.), circle(1., 0., 1.), circle(10., 0., 1.) ]);
+        assert_eq!(scene.component_count(), 2);
+        assert_eq!(scene.component_of(0), scene.component_of(1));
+        assert_ne!(scene.component_of(0), scene.component_of(2));
+    }
+
+    #[test]
+    fn containment() {
+        let scene = Scene::new(vec![ circle(0., 0., 3.), circle(0.5, 0., 1.) ]);
+        assert_eq!(scene.containment(), vec![ (1, 0) ]);
+        let scene = Scene::new(vec![ circle(0., 0., 1.), circle(0., 0., 3.), circle(0., 0., 2.), circle(5., 0., 1.) ]);
+        assert_eq!(scene.containment(), vec![ (0, 1), (0, 2), (2, 1) ]);
+        let scene = Scene::new(vec![ circle(0., 0., 1.), circle(1., 0., 1.) ]);
+        assert_eq!(scene.containment(), vec![]);
+    }
+
+    #[test]
+    fn region_at() {
+        let scene = Scene::new(vec![ circle(0., 0., 1.), circle(1., 0., 1.) ]);
+        assert_eq!(scene.region_at(R2 { x: -0.5, y: 0. }), Some("0-".to_string()));
+        assert_eq!(scene.region_at(R2 { x:  0.5, y: 0. }), Some("01".to_string()));
+        assert_eq!(scene.region_at(R2 { x:  1.5, y: 0. }), Some("-1".to_string()));
+        assert_eq!(scene.region_at(R2 { x:  0.5, y: 2. }), None);
+        // Annulus: between a nested circle and its container
+        let scene = Scene::new(vec![ circle(0., 0., 3.), circle(0., 0., 1.) ]);
+        assert_eq!(scene.region_at(R2 { x: 2., y: 0. }), Some("0-".to_string()));
+        assert_eq!(scene.region_at(R2 { x: 0., y: 0. }), Some("01".to_string()));
+    }
+
+    #[test]
+    fn region_adjacency() {
+        let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+        let scene = Scene::new(vec![ circle(0., 0., 1.), circle(1., 0., 1.) ]);
+        assert_eq!(scene.region_adjacency(), vec![ pair("-1", "01"), pair("0-", "01") ]);
+        // Circle 1 nested inside circle 0, with circle 2 crossing circle 0 only
+        let scene = Scene::new(vec![ circle(0., 0., 3.), circle(0., 0., 1.), circle(3., 0., 1.) ]);
+        assert_eq!(scene.region_adjacency(), vec![ pair("--2", "0-2"), pair("0--", "0-2"), pair("0--", "01-") ]);
+    }
+
+    #[test]
+    fn area_matrix() {
+        let scene = Scene::new(ellipses4(2.).into());
+        let areas = scene.area_matrix();
+        assert_eq!(areas.len(), 15);
+        assert_relative_eq!(areas.values().sum::<f64>(), scene.total_area(), max_relative = 1e-12);
+        for (key, area) in &areas {
+            assert_eq!(*area, scene.area(key).unwrap());
+        }
+    }
+
     #[test]
     fn ellipses4_0_2() {
         let shapes = ellipses4_select(2., [0, 2]).to_vec();
@@ -536,6 +1054,203 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_5_ellipses_verify_areas() {
+        // 5 ellipses rotated about the origin; each one's regions' areas should sum to the ellipse's area
+        let shapes: Vec<Shape<f64>> = (0..5).map(|idx| {
+            let t = 2. * PI * (idx as f64) / 5.;
+            xyrrt(0.6 * t.cos(), 0.6 * t.sin(), 2., 1., t)
+        }).collect();
+        let scene = Scene::new(shapes);
+        assert_eq!(scene.components.len(), 1);
+        let component = &scene.components[0];
+        assert_eq!(component.regions.len(), 41);
+        // `verify_areas` only logs mismatches, so also check the sums directly
+        component.verify_areas(1e-14).unwrap();
+        for set in &component.sets {
+            let set = set.borrow();
+            let region_areas = component.regions.iter().filter(|r| r.container_set_idxs.contains(&set.idx)).map(|r| r.total_area).kahan_sum();
+            assert_relative_eq!(region_areas, set.shape.area(), max_relative = 1e-14);
+        }
+    }
+
+    /// `copies` disjoint copies of the 5-ellipse layout from [`test_5_ellipses_verify_areas`], each with a small circle nested in its central (all-ellipse) region.
+    fn ellipses5_copies(copies: usize) -> Vec<Shape<f64>> {
+        (0..copies).flat_map(|copy| {
+            let cx = 10. * copy as f64;
+            (0..5).map(move |idx| {
+                let t = 2. * PI * (idx as f64) / 5.;
+                xyrrt(cx + 0.6 * t.cos(), 0.6 * t.sin(), 2., 1., t)
+            }).chain(std::iter::once(circle(cx + 0.03, 0.02, 0.1)))
+        }).collect()
+    }
+
+    #[test]
+    fn area_matrix_recomputed() {
+        let scene = Scene::new(ellipses5_copies(3));
+        assert_eq!(scene.components.len(), 6);
+        let recomputed = scene.area_matrix_recomputed();
+        // Nested circle's region, and the central region it is subtracted from
+        assert!(recomputed.contains_key(&format!("012345{}", "-".repeat(12))));
+        assert!(recomputed.contains_key(&format!("01234-{}", "-".repeat(12))));
+        assert_eq!(recomputed, scene.area_matrix());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn area_matrix_par() {
+        let scene = Scene::new(ellipses5_copies(3));
+        assert_eq!(scene.area_matrix_par(), scene.area_matrix_recomputed());
+    }
+
+    /// `cargo test --release --features parallel -- --ignored area_matrix_par_bench --nocapture`
+    #[cfg(feature = "parallel")]
+    #[test]
+    #[ignore]
+    fn area_matrix_par_bench() {
+        use std::time::Instant;
+        for copies in [ 1, 4, 16 ] {
+            let scene = Scene::new(ellipses5_copies(copies));
+            let reps = 200;
+            let start = Instant::now();
+            for _ in 0..reps { scene.area_matrix_recomputed(); }
+            let serial = start.elapsed();
+            let start = Instant::now();
+            for _ in 0..reps { scene.area_matrix_par(); }
+            let parallel = start.elapsed();
+            println!("{} components: serial {:?}, parallel {:?} (per call)", scene.components.len(), serial / reps, parallel / reps);
+        }
+    }
+
+    #[test]
+    fn normalized() {
+        let shapes = vec![
+            circle(0., 0., 1.),
+            xyrr(1.2, 0.3, 2., 0.7),
+            xyrrt(0.4, 1.1, 1.5, 0.8, 0.4),
+        ];
+        let scene = Scene::new(shapes.clone());
+        let normalized = scene.normalized();
+        assert_relative_eq!(normalized.total_area(), 1., epsilon = 1e-12);
+        let moved = Projection(vec![
+            Transform::Rotate(2.3),
+            Transform::Scale(1.7),
+            Transform::Translate(R2 { x: -3., y: 5. }),
+        ]);
+        let moved: Vec<Shape<f64>> = shapes.iter().map(|s| s.apply(&moved)).collect();
+        let moved = Scene::new(moved).normalized();
+        let vals = |scene: &Scene<f64>| scene.sets.iter().flat_map(|s| s.borrow().shape.vals()).collect::<Vec<f64>>();
+        let (expected, actual) = (vals(&normalized), vals(&moved));
+        assert_eq!(expected.len(), actual.len());
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            assert_relative_eq!(e, a, epsilon = 1e-9);
+        }
+        assert_eq!(normalized.area_matrix().keys().collect::<Vec<_>>(), moved.area_matrix().keys().collect::<Vec<_>>());
+        for ((_, e), (_, a)) in normalized.area_matrix().iter().zip(moved.area_matrix().iter()) {
+            assert_relative_eq!(e, a, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn region_keys() {
+        // Chain: A-B and B-C overlap, A and C don't
+        let scene = Scene::new(vec![ circle(0., 0., 1.), circle(1.5, 0., 1.), circle(3., 0., 1.) ]);
+        let keys = scene.region_keys();
+        assert_eq!(keys, vec![ "--2", "-1-", "-12", "0--", "01-" ]);
+        assert!(!keys.contains(&"0-2".to_string()));
+        assert!(!keys.contains(&"012".to_string()));
+        // Nested: the inner circle's only region is inside the outer one
+        let scene = Scene::new(vec![ circle(0., 0., 2.), circle(0.5, 0., 0.5), circle(5., 0., 1.) ]);
+        assert_eq!(scene.region_keys(), vec![ "--2", "0--", "01-" ]);
+    }
+
+    #[test]
+    fn boundary_paths() {
+        // Shoelace area of each region's boundary path, sampled densely, should match its (closed-form) area
+        let check = |shapes: Vec<Shape<f64>>, num_regions: usize| {
+            let scene = Scene::new(shapes);
+            let regions: Vec<&Region<f64>> = scene.components.iter().flat_map(|c| c.regions.iter()).collect();
+            assert_eq!(regions.len(), num_regions);
+            for region in regions {
+                let path = region.boundary_path();
+                let mut from = path.last().unwrap().to();
+                let mut points = vec![];
+                for seg in &path {
+                    points.extend(seg.sample(from, 2000));
+                    from = seg.to();
+                }
+                let area = points.iter().zip(points.iter().cycle().skip(1)).map(|(a, b)| a.x * b.y - a.y * b.x).sum::<f64>().abs() / 2.;
+                assert_relative_eq!(area, region.total_area, max_relative = 1e-5);
+            }
+        };
+        check(ellipses4(2.).to_vec(), 15);
+        check(ellipses5_copies(1), 42);
+        check(vec![ circle(0., 0., 1.), xyrrt(1., 0.5, 2., 1., 0.3) ], 3);
+        // Singletons (one arc spanning the whole shape) are split in two
+        let scene = Scene::new(vec![ circle(0., 0., 1.) ]);
+        let path = scene.components[0].regions[0].boundary_path();
+        assert_eq!(path.len(), 2);
+        let d = PathSeg::svg(&path);
+        assert!(d.starts_with("M 1 0 A 1 1 0 0 1 -1 ") && d.ends_with(" A 1 1 0 0 1 1 0 Z"), "{}", d);
+        check(vec![ xyrrt(0., 0., 2., 1., 0.3) ], 1);
+    }
+
+    #[test]
+    fn degenerate_pairs() {
+        let areas = |a: Shape<f64>, b: Shape<f64>| {
+            let scene = Scene::new(vec![ a, b ]);
+            [ "0-", "-1", "01" ].map(|key| scene.area(&key.to_string()).unwrap_or(0.))
+        };
+        let check = |a: Shape<f64>, b: Shape<f64>, expected: [f64; 3]| {
+            let actual = areas(a, b);
+            for (actual, expected) in actual.iter().zip(expected.iter()) {
+                assert_relative_eq!(actual, expected, epsilon = 1e-9);
+            }
+        };
+        // Identical (or equivalent) shapes fully overlap
+        check(xyrr(0., 0., 2., 1.), xyrr(0., 0., 2., 1.), [ 0., 0., 2. * PI ]);
+        check(xyrr(0., 0., 2., 1.), xyrrt(0., 0., 2., 1., PI), [ 0., 0., 2. * PI ]);
+        check(circle(0., 0., 1.), circle(0., 0., 1.), [ 0., 0., PI ]);
+        // Concentric, nested
+        check(xyrr(0., 0., 2., 1.), xyrr(0., 0., 1., 0.5), [ 1.5 * PI, 0., 0.5 * PI ]);
+        // Concentric, tangent at (0, ±1)
+        check(xyrr(0., 0., 2., 1.), circle(0., 0., 1.), [ PI, 0., PI ]);
+        check(circle(0., 0., 1.), xyrr(0., 0., 2., 1.), [ 0., PI, PI ]);
+
+        // Identical shapes, with gradients
+        let scene = Scene::new(Shapes::from_vec(&vec![ (xyrr(0., 0., 2., 1.), vec![ D, D, D, D ]), (xyrr(0., 0., 2., 1.), vec![ D, D, D, D ]) ]));
+        let area = scene.area(&"01".to_string()).unwrap();
+        assert_relative_eq!(area.v(), 2. * PI, epsilon = 1e-9);
+        assert!(area.d().iter().all(|d| d.is_finite()), "{:?}", area.d());
+    }
+
+    #[test]
+    fn relayout() {
+        let scene = |r: f64, dx: f64| {
+            let mut shapes = ellipses4(r);
+            if let Shape::XYRR(e) = &mut shapes[3] {
+                e.c.x += dx;
+            }
+            Scene::new(Shapes::from_vec(&shapes.into_iter().map(|s| (s, vec![ D, D, D, D ])).collect()))
+        };
+        let prev = scene(2., 0.);
+        let cur = scene(2.01, 0.02);
+        let relaid = prev.relayout(cur.sets.iter().map(|s| s.borrow().shape.clone()).collect()).unwrap();
+        let expected = cur.area_matrix();
+        let actual = relaid.area_matrix();
+        assert_eq!(actual.keys().collect::<Vec<_>>(), expected.keys().collect::<Vec<_>>());
+        for (key, area) in &actual {
+            let expected = &expected[key];
+            assert_relative_eq!(area.v(), expected.v(), epsilon = 1e-12);
+            for (d, e) in area.d().iter().zip(expected.d().iter()) {
+                assert_relative_eq!(d, e, epsilon = 1e-12);
+            }
+        }
+        // Topology change: shape 3 moves out of the others' way
+        let far = scene(2., 10.);
+        assert!(prev.relayout(far.sets.iter().map(|s| s.borrow().shape.clone()).collect()).is_none());
+    }
+
     #[test]
     fn test_4_circles_lattice_0_1() {
         let shapes = vec![
@@ -0,0 +1,58 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+/// Step-size ("learning rate", i.e. `max_step_error_ratio`) as a function of step index.
+#[derive(Debug, Clone, Copy, PartialEq, Tsify, Serialize, Deserialize)]
+pub enum LearningRateSchedule {
+    Constant(f64),
+    /// `initial * gamma^t`
+    ExponentialDecay { initial: f64, gamma: f64 },
+    /// Cosine-anneal from `initial` toward 0 over each `period` steps, then restart.
+    CosineAnnealing { initial: f64, period: usize },
+    /// Multiply by `drop` every `every` steps.
+    StepDecay { initial: f64, drop: f64, every: usize },
+}
+
+impl LearningRateSchedule {
+    pub fn rate(&self, step_idx: usize) -> f64 {
+        match *self {
+            LearningRateSchedule::Constant(rate) => rate,
+            LearningRateSchedule::ExponentialDecay { initial, gamma } => initial * gamma.powi(step_idx as i32),
+            LearningRateSchedule::CosineAnnealing { initial, period } => {
+                if period == 0 {
+                    return initial;
+                }
+                let t = (step_idx % period) as f64 / period as f64;
+                initial * (1. + (PI * t).cos()) / 2.
+            },
+            LearningRateSchedule::StepDecay { initial, drop, every } => {
+                if every == 0 {
+                    return initial;
+                }
+                initial * drop.powi((step_idx / every) as i32)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn rates() {
+        assert_eq!(LearningRateSchedule::Constant(0.5).rate(100), 0.5);
+        assert_eq!(LearningRateSchedule::ExponentialDecay { initial: 1., gamma: 0.5 }.rate(3), 0.125);
+        let cos = LearningRateSchedule::CosineAnnealing { initial: 1., period: 4 };
+        assert_eq!(cos.rate(0), 1.);
+        assert!((cos.rate(2) - 0.5).abs() < 1e-12);
+        assert_eq!(cos.rate(4), 1.);
+        let step = LearningRateSchedule::StepDecay { initial: 1., drop: 0.1, every: 10 };
+        assert_eq!(step.rate(9), 1.);
+        assert_eq!(step.rate(10), 0.1);
+    }
+
+}
@@ -0,0 +1,38 @@
+use schemars::schema_for;
+
+use crate::{shape::InputSpec, targets::TargetsMap};
+
+/// JSON Schema (pretty-printed) for a shape-inputs file: an array of `[shape, trainable]` pairs (see [`InputSpec`]), where each shape is an externally-tagged `Circle`, `XYRR`, or `XYRRT` object.
+pub fn inputs() -> String {
+    serde_json::to_string_pretty(&schema_for!(Vec<InputSpec>)).unwrap()
+}
+
+/// JSON Schema (pretty-printed) for a targets file: an object mapping region keys (e.g. `"0*"`, `"01-"`) to sizes (see [`TargetsMap`]).
+pub fn targets() -> String {
+    serde_json::to_string_pretty(&schema_for!(TargetsMap<f64>)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use test_log::test;
+
+    #[test]
+    fn inputs_schema() {
+        let schema: Value = serde_json::from_str(&inputs()).unwrap();
+        assert_eq!(schema["type"], "array");
+        // One variant per shape kind, each an object with a single required key naming the kind
+        let variants = schema["definitions"]["Shape_for_double"]["oneOf"].as_array().unwrap();
+        let kinds: Vec<&str> = variants.iter().map(|variant| variant["required"][0].as_str().unwrap()).collect();
+        assert_eq!(kinds, vec![ "Circle", "XYRR", "XYRRT" ]);
+        assert!(schema["definitions"]["XYRRT_for_double"]["properties"]["t"].is_object());
+    }
+
+    #[test]
+    fn targets_schema() {
+        let schema: Value = serde_json::from_str(&targets()).unwrap();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["additionalProperties"]["type"], "number");
+    }
+}
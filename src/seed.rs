@@ -0,0 +1,116 @@
+use std::f64::consts::PI;
+
+use nalgebra::{DMatrix, SymmetricEigen};
+
+use crate::{shape::{circle, xyrr, xyrrt, InputSpec, ShapeKind}, targets::{Targets, TargetsMap}};
+
+/// Bisection iterations used by [`lens_distance`].
+pub const LENS_DISTANCE_ITERS: usize = 60;
+
+/// Area of the intersection of two circles with radii `r0`, `r1` whose centers are `d` apart.
+pub fn lens_area(r0: f64, r1: f64, d: f64) -> f64 {
+    if d >= r0 + r1 {
+        0.
+    } else if d <= (r0 - r1).abs() {
+        PI * r0.min(r1).powi(2)
+    } else {
+        let a0 = ((d * d + r0 * r0 - r1 * r1) / (2. * d * r0)).clamp(-1., 1.).acos();
+        let a1 = ((d * d + r1 * r1 - r0 * r0) / (2. * d * r1)).clamp(-1., 1.).acos();
+        let k = ((-d + r0 + r1) * (d + r0 - r1) * (d - r0 + r1) * (d + r0 + r1)).max(0.).sqrt();
+        r0 * r0 * a0 + r1 * r1 * a1 - k / 2.
+    }
+}
+
+/// Distance between the centers of circles with radii `r0`, `r1` at which their intersection has area `overlap` (clamped to the achievable range, so `0` gives tangent circles and `≥ π·min(r0, r1)²` gives one containing the other).
+pub fn lens_distance(r0: f64, r1: f64, overlap: f64) -> f64 {
+    let (mut lo, mut hi) = ((r0 - r1).abs(), r0 + r1);
+    if overlap <= 0. {
+        return hi;
+    }
+    if overlap >= PI * r0.min(r1).powi(2) {
+        return lo;
+    }
+    // `lens_area` decreases monotonically between fully-contained (`lo`) and tangent (`hi`)
+    for _ in 0..LENS_DISTANCE_ITERS {
+        let mid = (lo + hi) / 2.;
+        if lens_area(r0, r1, mid) > overlap {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.
+}
+
+/// Classical multidimensional scaling: 2D points whose pairwise distances approximate `distances` (a symmetric matrix).
+pub fn classical_mds(distances: &DMatrix<f64>) -> Vec<(f64, f64)> {
+    let n = distances.nrows();
+    let sq = distances.map(|d| d * d);
+    let centering = DMatrix::<f64>::identity(n, n) - DMatrix::<f64>::from_element(n, n, 1. / n as f64);
+    let gram = &centering * sq * &centering * -0.5;
+    let eigen = SymmetricEigen::new(gram);
+    let mut idxs: Vec<usize> = (0..n).collect();
+    idxs.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+    let coord = |point: usize, axis: usize| {
+        idxs.get(axis).map_or(0., |&k| eigen.eigenvectors[(point, k)] * eigen.eigenvalues[k].max(0.).sqrt())
+    };
+    (0..n).map(|point| (coord(point, 0), coord(point, 1))).collect()
+}
+
+/// Initial layout for the sets in `targets`: circle-equivalent shapes whose areas match each set's target area, with centers placed (via [`classical_mds`]) so that pairwise overlaps roughly match pairwise intersection targets. All coordinates are trainable.
+pub fn seed_layout(targets: &TargetsMap<f64>, shape_kind: ShapeKind) -> Vec<InputSpec> {
+    let targets = Targets::new(targets.clone());
+    let n = targets.n;
+    let key = |idxs: &[usize]| -> String {
+        (0..n).map(|i| if idxs.contains(&i) { Targets::<f64>::idx(i) } else { '*' }).collect()
+    };
+    let radii: Vec<f64> = (0..n).map(|i| (targets[&key(&[i])].max(0.) / PI).sqrt()).collect();
+    let mut distances = DMatrix::<f64>::zeros(n, n);
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let d = lens_distance(radii[i], radii[j], targets[&key(&[i, j])]);
+            distances[(i, j)] = d;
+            distances[(j, i)] = d;
+        }
+    }
+    classical_mds(&distances).into_iter().zip(radii).map(|((x, y), r)| {
+        match shape_kind {
+            ShapeKind::Circle => (circle(x, y, r), vec![ true; 3 ]),
+            ShapeKind::XYRR => (xyrr(x, y, r, r), vec![ true; 4 ]),
+            ShapeKind::XYRRT => (xyrrt(x, y, r, r, 0.), vec![ true; 5 ]),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn lens() {
+        assert_eq!(lens_area(1., 1., 2.), 0.);
+        assert_eq!(lens_area(2., 1., 0.5), PI);
+        let d = lens_distance(1., 1., PI / 4.);
+        assert_relative_eq!(lens_area(1., 1., d), PI / 4., epsilon = 1e-12);
+        assert_eq!(lens_distance(1., 2., 0.), 3.);
+        assert_eq!(lens_distance(1., 2., 10.), 1.);
+    }
+
+    #[test]
+    fn mds() {
+        // 3-4-5 right triangle
+        let distances = DMatrix::from_row_slice(3, 3, &[
+            0., 3., 4.,
+            3., 0., 5.,
+            4., 5., 0.,
+        ]);
+        let points = classical_mds(&distances);
+        let dist = |a: (f64, f64), b: (f64, f64)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+        for i in 0..3 {
+            for j in 0..3 {
+                assert_relative_eq!(dist(points[i], points[j]), distances[(i, j)], epsilon = 1e-9);
+            }
+        }
+    }
+}
@@ -14,12 +14,21 @@ pub type Input = (Shape<f64>, Duals);
 pub type InputSpec = (Shape<f64>, Vec<bool>);
 
 #[derive(Debug, Display, Clone, From, PartialEq, Serialize, Deserialize, Tsify)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Shape<D> {
     Circle(circle::Circle<D>),
     XYRR(xyrr::XYRR<D>),
     XYRRT(xyrrt::XYRRT<D>),
 }
 
+/// Which [`Shape`] variant to construct (e.g. in [`crate::seed::seed_layout`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+pub enum ShapeKind {
+    Circle,
+    XYRR,
+    XYRRT,
+}
+
 pub struct Shapes {}
 impl Shapes {
     pub fn from<const N: usize>(input_specs: [InputSpec; N]) -> [ Shape<D>; N ] {
@@ -122,6 +131,14 @@ impl Shape<f64> {
             Shape::XYRRT(e) => e.vals().to_vec(),
         }
     }
+    /// Length of the boundary (exact for circles; ellipses use Ramanujan's approximation, see [`xyrr::XYRR::perimeter`]).
+    pub fn perimeter(&self) -> f64 {
+        match self {
+            Shape::Circle(c) => c.perimeter(),
+            Shape::XYRR(e) => e.perimeter(),
+            Shape::XYRRT(e) => e.perimeter(),
+        }
+    }
 }
 
 impl<D: Clone> Shape<D> {
@@ -235,10 +252,17 @@ impl<D: LevelArg + UnitCircleGap> Shape<D> {
             Shape::XYRRT(e) => e.unit_circle_gap(),
         }
     }
+    pub fn unit_circle_overlap(&self) -> Option<D> {
+        match self {
+            Shape::Circle(c) => c.unit_circle_overlap(),
+            Shape::XYRR(e) => e.unit_circle_overlap(),
+            Shape::XYRRT(e) => e.unit_circle_overlap(),
+        }
+    }
 }
 
 impl Shape<Dual> {
-    pub fn step(&self, step_vec: &Vec<f64>) -> Shape<Dual> {
+    pub fn step(&self, step_vec: &[f64]) -> Shape<Dual> {
         match self {
             Shape::Circle(s) => {
                 let [ dx, dy, dr ]: [f64; 3] = s.duals().map(|d| d.iter().zip(step_vec).map(|(mask, step)| mask * step).sum());
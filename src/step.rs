@@ -1,15 +1,22 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
 
+use anyhow::{Result, anyhow};
+use itertools::Itertools;
 use log::{info, debug, warn};
+use nalgebra::DMatrix;
 use serde::{Deserialize, Serialize};
 use tsify::{declare, Tsify};
 
 use crate::math::recip::Recip;
+use crate::path::PathSeg;
 use crate::shape::{Shape, Shapes, InputSpec};
-use crate::{distance::Distance, scene::Scene, math::is_zero::IsZero, r2::R2, targets::Targets, regions};
+use crate::{distance::Distance, fmt::round_sig, gap::{Gap, Overlap}, loss::LossConfig, scene::Scene, math::is_zero::IsZero, r2::R2, targets::Targets, regions};
 use crate::dual::{Dual, D};
 
+/// Coordinate perturbation used by [`Step::fd_hessian`]'s central differences of the gradient.
+pub const FD_HESSIAN_STEP: f64 = 1e-5;
+
 #[declare]
 pub type Errors = BTreeMap<String, Error>;
 
@@ -21,6 +28,11 @@ pub struct Step {
     pub total_area: Dual,
     pub errors: Errors,
     pub error: Dual,
+    #[serde(default)]
+    pub loss: LossConfig,
+    /// Name of the coordinate each gradient slot is with respect to (see [`Self::named_gradients`])
+    #[serde(default)]
+    pub grad_names: Vec<String>,
 }
 
 #[derive(Clone, Debug, Tsify, Serialize, Deserialize)]
@@ -33,6 +45,56 @@ pub struct Error {
     pub error: Dual,
 }
 
+/// A region's total area (summed across components), with its target area and error (when it has a target).
+#[derive(Clone, Debug, PartialEq, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionArea {
+    pub key: String,
+    pub area: f64,
+    pub target_area: Option<f64>,
+    pub error: Option<f64>,
+}
+
+/// One region's contribution to a [`Step`]'s error (see [`Step::error_breakdown`]).
+#[derive(Clone, Debug, PartialEq, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionError {
+    pub key: String,
+    /// Actual area (0 if the region doesn't exist)
+    pub actual: f64,
+    pub target: f64,
+    /// This region's (weighted) term in the total error
+    pub abs_error: f64,
+    /// `(actual - target) / target`, or `None` for zero targets
+    pub rel_error: Option<f64>,
+    /// `abs_error` as a percentage of the sum of all regions' `abs_error`s
+    pub pct_of_total: f64,
+}
+
+/// Goodness-of-fit of a [`Step`]'s region areas to their targets, both as fractions of their respective totals (see [`Step::fit_metrics`]).
+#[derive(Clone, Debug, PartialEq, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FitMetrics {
+    /// `Σ|actual - target|`
+    pub total_abs_error: f64,
+    /// Mean of `|actual - target| / target`, over regions with nonzero targets
+    pub mean_rel_error: f64,
+    /// Max of `|actual - target| / target`, over regions with nonzero targets
+    pub max_rel_error: f64,
+    /// `Σ min(actual, target) / Σ max(actual, target)`: 1 for a perfect fit, 0 if no region overlaps its target
+    pub weighted_jaccard: f64,
+}
+
+/// Compact summary of a [`Step`]: total area, error, and each set's area and fraction of the total.
+#[derive(Clone, Debug, PartialEq, Tsify, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepSummary {
+    pub total_area: f64,
+    pub error: f64,
+    pub per_set_area: Vec<f64>,
+    pub per_set_frac: Vec<f64>,
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f,
@@ -47,14 +109,33 @@ impl Display for Error {
 
 impl Step {
     pub fn new(input_specs: Vec<InputSpec>, targets: Targets<f64>) -> Step {
+        Step::new_with_loss(input_specs, targets, LossConfig::default())
+    }
+    pub fn new_with_loss(input_specs: Vec<InputSpec>, targets: Targets<f64>, loss: LossConfig) -> Step {
         let shapes = Shapes::from_vec(&input_specs);
-        Step::nxt(shapes, targets)
+        Step::nxt_with_loss(shapes, targets, loss)
     }
     pub fn nxt(shapes: Vec<Shape<D>>, targets: Targets<f64>) -> Step {
-        let scene = Scene::new(shapes);
+        Step::nxt_with_loss(shapes, targets, LossConfig::default())
+    }
+    pub fn nxt_with_loss(shapes: Vec<Shape<D>>, targets: Targets<f64>, loss: LossConfig) -> Step {
+        Step::from_scene(&Scene::new(shapes), targets, loss)
+    }
+    /// Like [`Self::nxt_with_loss`], but if `prev` (the previous step's [`Scene`]) has the same topology as `shapes`, reuse it (see [`Scene::relayout`]) instead of building a new [`Scene`] from scratch. Returns the [`Scene`] along with the [`Step`], for use with the next step.
+    pub fn nxt_cached(shapes: Vec<Shape<D>>, targets: Targets<f64>, loss: LossConfig, prev: Option<&Scene<D>>) -> (Step, Scene<D>) {
+        let scene = match prev.and_then(|prev| prev.relayout(shapes.clone())) {
+            Some(scene) => scene,
+            None => {
+                debug!("Topology changed, rebuilding scene");
+                Scene::new(shapes)
+            },
+        };
+        (Step::from_scene(&scene, targets, loss), scene)
+    }
+    fn from_scene(scene: &Scene<D>, targets: Targets<f64>, loss: LossConfig) -> Step {
         let sets = &scene.sets;
         let all_key = String::from_utf8(vec![b'*'; scene.len()]).unwrap();
-        let total_area = scene.area(&all_key).unwrap_or_else(|| scene.zero());
+        let total_area = if loss.clamp_negative_areas { scene.area_clamped(&all_key) } else { scene.area(&all_key) }.unwrap_or_else(|| scene.zero());
         debug!("scene: {} components, total_area {}, component sizes {}", scene.components.len(), total_area, scene.components.iter().map(|c| c.sets.len().to_string()).collect::<Vec<_>>().join(", "));
         for component in &scene.components {
             debug!("  {} regions", component.regions.len());
@@ -62,15 +143,9 @@ impl Step {
                 debug!("    {}: {} segments, area {}", region.key, region.segments.len(), region.area());
             }
         }
-        let errors = Self::compute_errors(&scene, &targets, &total_area);
+        let errors = Self::compute_errors(scene, &targets, &total_area, &loss);
         let disjoint_targets = targets.disjoints();
-        let mut error = scene.zero();
-        for key in disjoint_targets.keys() {
-            let e = errors.get(key).unwrap();
-            let err = e.error.abs();
-            debug!("  {}: error {}, {}", key, e, err);
-            error += err;
-        }
+        let mut error = Self::disjoint_error(scene, &targets, &errors);
         // let mut error: D = disjoint_targets.iter().map(|(key, _)| errors.get(key).unwrap().error.abs()).sum();
         debug!("step error {:?}", error);
         // Optional/Alternate loss function based on per-region squared errors, weights errors by region size:
@@ -106,6 +181,12 @@ impl Step {
             let parents_key = key.replace('-', "*");
             let parent_regions_exist = errors.get(&parents_key).unwrap().actual_area.clone().filter(|a| !a.is_zero()).is_some();
             debug!("missing region {:?}, centroid {:?}, parents {} ({})", set_idxs, centroid, parents_key, parent_regions_exist);
+            let gap = if loss.gap_penalty && !parent_regions_exist {
+                let shapes: Vec<Shape<D>> = sets.iter().map(|s| s.borrow().shape.clone()).collect();
+                region_gap(&shapes, &set_idxs)
+            } else {
+                None
+            };
             if parent_regions_exist {
                 let mut parents = Vec::<usize>::new();
                 for (idx, ch) in parents_key.char_indices() {
@@ -130,6 +211,10 @@ impl Step {
                     }
                 }
                 total_missing_contained += target;
+            } else if let Some(gap) = gap {
+                debug!("  missing region penalty: {}, gap {}", key, &gap);
+                total_disjoint_penalty += gap * target;
+                total_missing_disjoint += target;
             } else {
                 set_idxs.iter().for_each(|idx| {
                     let set = &sets[*idx];
@@ -159,11 +244,28 @@ impl Step {
             error += Dual::new(0., total_contained_penalty.d());
         }
 
+        if loss.disjoint_penalty {
+            let shapes: Vec<Shape<D>> = sets.iter().map(|s| s.borrow().shape.clone()).collect();
+            // Unlike the missing-region penalties above, this contributes to the error's value (not just its gradient): the area errors of shrinking overlaps vanish faster than their depths, which would otherwise make for ever-smaller steps.
+            let overlaps = unwanted_overlaps(&shapes, &targets, &errors);
+            if !overlaps.is_empty() {
+                let total_overlap_penalty = overlaps.into_values().fold(scene.zero(), |sum, depth| sum + depth) / targets.total_area.sqrt();
+                debug!("  total_overlap_penalty: {}", total_overlap_penalty);
+                error += total_overlap_penalty;
+            }
+        }
+
+        if let Some(target_total) = loss.total_area {
+            let total_area_error = (total_area.clone() - target_total).abs() / target_total;
+            debug!("  total_area_error: {}", total_area_error);
+            error += total_area_error;
+        }
+
         // Take shapes back from `scene`
         let shapes = sets.into_iter().map(|s| s.borrow().to_owned().shape).collect::<Vec<Shape<D>>>();
 
         debug!("all-in error: {:?}", error);
-        Step { shapes, components, targets, total_area, errors, error }
+        Step { shapes, components, targets, total_area, errors, error, loss, grad_names: vec![] }.with_grad_names(&[])
     }
 
     pub fn n(&self) -> usize {
@@ -174,16 +276,49 @@ impl Step {
         self.error.1
     }
 
-    pub fn compute_errors(scene: &Scene<D>, targets: &Targets<f64>, total_area: &Dual) -> Errors {
+    /// Error of the scene described by `input_specs`, without constructing a full [`Step`]: no coordinates are differentiated, and the display-oriented [`regions::Component`]s and missing-region penalties (which only contribute gradients) are skipped. Matches `Step::new(input_specs, targets).error.v()`; useful for cheaply scoring many candidate layouts (roughly 2.5x faster than [`Step::new`] for 3 ellipses with all 12 coordinates trainable).
+    pub fn error_only(input_specs: Vec<InputSpec>, targets: Targets<f64>) -> Result<f64> {
+        if input_specs.len() != targets.n {
+            return Err(anyhow!("{} shapes, but targets are for {} sets", input_specs.len(), targets.n));
+        }
+        let frozen: Vec<InputSpec> = input_specs.into_iter().map(|(shape, spec)| (shape, vec![false; spec.len()])).collect();
+        let scene = Scene::new(Shapes::from_vec(&frozen));
+        let all_key = String::from_utf8(vec![b'*'; scene.len()]).unwrap();
+        let total_area = scene.area(&all_key).unwrap_or_else(|| scene.zero());
+        let errors = Self::compute_errors(&scene, &targets, &total_area, &LossConfig::default());
+        Ok(Self::disjoint_error(&scene, &targets, &errors).v())
+    }
+
+    /// Sum of (weighted) absolute errors of disjoint target regions.
+    fn disjoint_error(scene: &Scene<D>, targets: &Targets<f64>, errors: &Errors) -> Dual {
+        let weights = targets.disjoint_weights();
+        let mut error = scene.zero();
+        for key in targets.disjoints().keys() {
+            let e = errors.get(key).unwrap();
+            let mut err = e.error.abs();
+            if let Some(weights) = &weights {
+                err = err * weights[key];
+            }
+            debug!("  {}: error {}, {}", key, e, err);
+            error += err;
+        }
+        error
+    }
+
+    /// Each target region's error: the difference between its actual and target fractions of the total area, scaled per `loss` (see [`LossConfig`]).
+    pub fn compute_errors(scene: &Scene<D>, targets: &Targets<f64>, total_area: &Dual, loss: &LossConfig) -> Errors {
         let none_key = targets.none_key();
         targets.iter().filter_map(|(key, target_area)| {
             if key == &none_key {
                 None
             } else {
-                let actual_area = scene.area(key);
+                let actual_area = if loss.clamp_negative_areas { scene.area_clamped(key) } else { scene.area(key) };
                 let target_frac = target_area / targets.total_area;
                 let actual_frac = actual_area.clone().unwrap_or_else(|| scene.zero()).clone() / total_area;
-                let error = actual_frac.clone() - target_frac;
+                let mut error = actual_frac.clone() - target_frac;
+                if loss.relative_weight != 0. {
+                    error = error * loss.scale(target_frac);
+                }
                 Some((
                     key.clone(),
                     Error {
@@ -204,6 +339,11 @@ impl Step {
     // }
 
     pub fn step(&self, max_step_error_ratio: f64) -> Step {
+        self.step_by(&self.step_vec(max_step_error_ratio))
+    }
+
+    /// Gradient-descent step vector, scaled so that its length is `max_step_error_ratio` times the current error.
+    pub fn step_vec(&self, max_step_error_ratio: f64) -> Vec<f64> {
         let error = self.error.clone();
         // let error = self.errors.values().into_iter().map(|e| e.error.clone() * &e.error).sum::<D>().sqrt();
         let error_size = &error.v();
@@ -217,11 +357,501 @@ impl Step {
         debug!("  err {:?}", error);
         debug!("  step_size {}, magnitude {}, grad_scale {}", step_size, magnitude, grad_scale);
         debug!("  step_vec {:?}", step_vec);
+        step_vec
+    }
+
+    /// Fixed-learning-rate step vector `-lr·∇error`, with each coordinate's move clipped to `±per_coord`, and the whole vector then scaled down (if necessary) to length `max`. Clipping coordinates independently bends the step toward the axes of the clipped coordinates.
+    pub fn step_clipped_vec(&self, lr: f64, per_coord: f64, max: f64) -> Vec<f64> {
+        let step_vec: Vec<f64> = self.error.d().iter().map(|d| (-lr * d).clamp(-per_coord, per_coord)).collect();
+        clip_l2(step_vec, max)
+    }
+
+    pub fn step_clipped(&self, lr: f64, per_coord: f64, max: f64) -> Step {
+        self.step_by(&self.step_clipped_vec(lr, per_coord, max))
+    }
+
+    /// Fixed-learning-rate step vector `-lr·∇error`, scaled down as a whole if its length exceeds `max_norm`. Unlike [`Self::step_clipped_vec`], this preserves the step's direction, so coordinated moves (e.g. a shape's center and radii together) stay coordinated.
+    pub fn step_clipped_l2_vec(&self, lr: f64, max_norm: f64) -> Vec<f64> {
+        clip_l2(self.error.d().iter().map(|d| -lr * d).collect(), max_norm)
+    }
+
+    pub fn step_clipped_l2(&self, lr: f64, max_norm: f64) -> Step {
+        self.step_by(&self.step_clipped_l2_vec(lr, max_norm))
+    }
+
+    /// Move each shape's differentiable coordinates by the corresponding element of `step_vec` (which should have length [`Self::grad_size`]), and compute the resulting [`Step`].
+    pub fn step_by(&self, step_vec: &[f64]) -> Step {
         let shapes = &self.shapes;
-        let new_shapes = shapes.iter().map(|s| s.step(&step_vec)).collect::<Vec<Shape<D>>>();
+        let new_shapes = shapes.iter().map(|s| s.step(step_vec)).collect::<Vec<Shape<D>>>();
         for (cur, nxt) in shapes.iter().zip(new_shapes.iter()) {
             debug!("  {} -> {:?}", cur.v(), nxt.v());
         }
-        Step::nxt(new_shapes, self.targets.clone())
+        Step::nxt_with_loss(new_shapes, self.targets.clone(), self.loss).with_grad_names(&self.grad_names)
+    }
+
+    /// Like [`Self::step_by`], but reuse `scene` (this step's [`Scene`], if known) when the new shapes' topology is unchanged (see [`Self::nxt_cached`]).
+    pub fn step_by_cached(&self, step_vec: &[f64], scene: Option<&Scene<D>>) -> (Step, Scene<D>) {
+        let new_shapes = self.shapes.iter().map(|s| s.step(step_vec)).collect::<Vec<Shape<D>>>();
+        let (step, scene) = Step::nxt_cached(new_shapes, self.targets.clone(), self.loss, scene);
+        (step.with_grad_names(&self.grad_names), scene)
+    }
+
+    /// Finite-difference approximation of the Hessian of [`Self::error`] w.r.t. the trainable coordinates (see [`Self::fd_hessian_of`]); there's no second-order dual type, so second derivatives aren't exact.
+    pub fn fd_hessian(&self) -> DMatrix<f64> {
+        self.fd_hessian_of(|step| step.error.d())
+    }
+
+    /// Half the sum of squared (disjoint-region) errors: a smooth alternative to [`Self::error`] (which sums absolute errors), better suited to second-order methods. Missing-region penalties aren't included.
+    pub fn sq_error(&self) -> Dual {
+        let mut sq_error = Dual::zero(self.grad_size());
+        for key in self.targets.disjoints().keys() {
+            let e = &self.errors[key].error;
+            sq_error += e * e;
+        }
+        sq_error * 0.5
+    }
+
+    /// Finite-difference approximation of the Hessian of [`Self::sq_error`] w.r.t. the trainable coordinates (see [`Self::fd_hessian_of`]).
+    pub fn fd_sq_error_hessian(&self) -> DMatrix<f64> {
+        self.fd_hessian_of(|step| step.sq_error().d())
+    }
+
+    /// Approximate Hessian of a quantity whose (exact, forward-mode) gradient is returned by `grad`, via central differences of `grad` (with step [`FD_HESSIAN_STEP`]), symmetrized. Requires `2 * grad_size` extra [`Step`] evaluations, so it's kept off the gradient-descent hot path.
+    pub fn fd_hessian_of<F: Fn(&Step) -> Vec<f64>>(&self, grad: F) -> DMatrix<f64> {
+        let n = self.grad_size();
+        let mut hessian = DMatrix::zeros(n, n);
+        let mut step_vec = vec![0.; n];
+        for j in 0..n {
+            step_vec[j] = FD_HESSIAN_STEP;
+            let fwd = grad(&self.step_by(&step_vec));
+            step_vec[j] = -FD_HESSIAN_STEP;
+            let bwd = grad(&self.step_by(&step_vec));
+            step_vec[j] = 0.;
+            for i in 0..n {
+                hessian[(i, j)] = (fwd[i] - bwd[i]) / (2. * FD_HESSIAN_STEP);
+            }
+        }
+        (&hessian + hessian.transpose()) / 2.
+    }
+
+    /// Round each shape's coordinates to `digits` significant digits (preserving which coordinates are trainable), and recompute the [`Step`]. Absorbs last-bit floating-point differences between platforms (e.g. native vs. WASM) before comparing steps.
+    pub fn canonicalize(&mut self, digits: usize) {
+        let shapes = self.shapes.iter().map(|shape| {
+            let vals = shape.v();
+            let names = vals.names();
+            let coords = names.iter().map(|name| name.as_str()).zip(vals.vals().into_iter().map(|v| round_sig(v, digits))).collect();
+            Shape::from_coords(coords).dual(&shape.duals())
+        }).collect();
+        *self = Step::nxt_with_loss(shapes, self.targets.clone(), self.loss).with_grad_names(&self.grad_names);
+    }
+
+    /// For each missing region (positive target, but zero actual area) whose member shapes are separated, the largest gap between a pair of its members (see [`region_gap`]). Regions that are missing for other reasons (e.g. one member contained in a non-member) aren't included.
+    pub fn missing_region_gaps(&self) -> BTreeMap<String, Dual> {
+        self.targets.disjoints().into_iter().filter(|(key, target)| {
+            *target > 0. && self.errors[key].actual_area.filter(|a| !a.is_zero()).is_none()
+        }).filter_map(|(key, _)| {
+            let set_idxs: Vec<usize> = key.chars().enumerate().filter(|(_, c)| *c != '-').map(|(idx, _)| idx).collect();
+            region_gap(&self.shapes, &set_idxs).map(|gap| (key, gap))
+        }).collect()
+    }
+
+    /// Overlap depths of regions that exist, but shouldn't (see [`unwanted_overlaps`]).
+    pub fn unwanted_overlaps(&self) -> BTreeMap<String, Dual> {
+        unwanted_overlaps(&self.shapes, &self.targets, &self.errors)
+    }
+
+    /// Values of [`Self::missing_region_gaps`]: how far each missing region's member shapes are from overlapping.
+    pub fn missing_region_distance(&self) -> BTreeMap<String, f64> {
+        self.missing_region_gaps().into_iter().map(|(key, gap)| (key, gap.v())).collect()
+    }
+
+    /// Total area, error, and per-set areas and fractions (from [`Self::errors`]).
+    pub fn summary(&self) -> StepSummary {
+        let n = self.shapes.len();
+        let (per_set_area, per_set_frac) = (0..n).map(|idx| {
+            let key: String = (0..n).map(|i| if i == idx { Targets::<f64>::idx(i) } else { '*' }).collect();
+            let e = &self.errors[&key];
+            (e.actual_area.unwrap_or(0.), e.actual_frac)
+        }).unzip();
+        StepSummary {
+            total_area: self.total_area.v(),
+            error: self.error.v(),
+            per_set_area,
+            per_set_frac,
+        }
+    }
+
+    /// Areas of all non-empty regions, keyed and sorted by exclusive region key (e.g. `"01-"`).
+    pub fn region_areas(&self) -> Vec<RegionArea> {
+        let mut areas: BTreeMap<String, f64> = BTreeMap::new();
+        for region in self.components.iter().flat_map(|c| c.regions.iter()) {
+            *areas.entry(region.key.clone()).or_insert(0.) += region.area;
+        }
+        areas.into_iter().map(|(key, area)| {
+            let target_area = self.targets.get(&key).cloned();
+            let error = self.errors.get(&key).map(|e| e.error.v());
+            RegionArea { key, area, target_area, error }
+        }).collect()
+    }
+
+    /// Interpretable, scale-free measures of how well each exclusive region's fraction of the total area matches its target fraction. Unlike [`Self::error`], these ignore [`LossConfig`] and [`Targets::weights`].
+    pub fn fit_metrics(&self) -> FitMetrics {
+        let fracs: Vec<(f64, f64)> = self.targets.disjoints().keys().map(|key| {
+            let e = &self.errors[key];
+            (e.actual_frac.max(0.), e.target_frac)
+        }).collect();
+        let total_abs_error = fracs.iter().map(|(a, t)| (a - t).abs()).sum();
+        let rel_errors: Vec<f64> = fracs.iter().filter(|(_, t)| *t > 0.).map(|(a, t)| (a - t).abs() / t).collect();
+        let mean_rel_error = if rel_errors.is_empty() { 0. } else { rel_errors.iter().sum::<f64>() / rel_errors.len() as f64 };
+        let max_rel_error = rel_errors.iter().cloned().fold(0., f64::max);
+        let (mins, maxs) = fracs.iter().fold((0., 0.), |(mins, maxs), (a, t)| (mins + a.min(*t), maxs + a.max(*t)));
+        let weighted_jaccard = if maxs > 0. { mins / maxs } else { 1. };
+        FitMetrics { total_abs_error, mean_rel_error, max_rel_error, weighted_jaccard }
+    }
+
+    /// Each exclusive region's contribution to the error, largest first: the same per-region terms (including [`Targets::weights`]) that [`Step::new`] sums into [`Self::error`], before any penalties.
+    pub fn error_breakdown(&self) -> Vec<RegionError> {
+        let weights = self.targets.disjoint_weights();
+        let mut breakdown: Vec<RegionError> = self.targets.disjoints().into_iter().map(|(key, target)| {
+            let e = &self.errors[&key];
+            let weight = weights.as_ref().map_or(1., |weights| weights[&key]);
+            let actual = e.actual_area.unwrap_or(0.);
+            RegionError {
+                abs_error: e.error.v().abs() * weight,
+                rel_error: if target == 0. { None } else { Some((actual - target) / target) },
+                actual,
+                target,
+                key,
+                pct_of_total: 0.,
+            }
+        }).collect();
+        let total: f64 = breakdown.iter().map(|e| e.abs_error).sum();
+        if total > 0. {
+            breakdown.iter_mut().for_each(|e| e.pct_of_total = 100. * e.abs_error / total);
+        }
+        breakdown.sort_by(|a, b| b.abs_error.total_cmp(&a.abs_error).then_with(|| a.key.cmp(&b.key)));
+        breakdown
+    }
+
+    /// Exclusive keys of the regions present in this step's geometry, sorted (see [`Scene::region_keys`]).
+    pub fn region_keys(&self) -> Vec<String> {
+        self.components.iter().flat_map(|c| c.regions.iter().map(|r| r.key.clone())).collect::<BTreeSet<_>>().into_iter().collect()
+    }
+
+    /// SVG path `d` attribute for each region's outer boundary (see [`crate::region::Region::boundary_path`]), in component order.
+    pub fn region_svg_paths(&self) -> Vec<(String, String)> {
+        self.components.iter().flat_map(|c| c.regions.iter()).map(|r| (r.key.clone(), PathSeg::svg(&r.path))).collect()
+    }
+
+    /// Each partial derivative of the error, paired with the name of the coordinate it is with respect to, in gradient order (see [`Self::with_grad_names`]).
+    pub fn named_gradients(&self) -> Vec<(String, f64)> {
+        self.grad_names.iter().cloned().zip(self.error.d()).collect()
+    }
+
+    /// This step, with each gradient slot named by the coordinate it is with respect to. Names match [`CoordGetters`](crate::coord_getter::CoordGetters) (`"<shape idx>.<coord>"`, e.g. `"0.r"`, `"1.cx"`); each trainable coordinate's dual is a unit vector, whose nonzero index is its position in the gradient. A slot with no nonzero dual (a coordinate frozen by [`Model::set_trainable`](crate::model::Model::set_trainable)) keeps its name from `prev` (the names recorded when it was seeded), or is named by its index if `prev` doesn't cover it.
+    pub fn with_grad_names(mut self, prev: &[String]) -> Step {
+        let mut names: Vec<Option<String>> = vec![None; self.grad_size()];
+        for (shape_idx, shape) in self.shapes.iter().enumerate() {
+            for (name, d) in shape.v().names().iter().zip(shape.duals()) {
+                if let Some(grad_idx) = d.iter().position(|v| *v != 0.) {
+                    names[grad_idx] = Some(format!("{}.{}", shape_idx, name));
+                }
+            }
+        }
+        self.grad_names = names.into_iter().enumerate().map(|(grad_idx, name)| {
+            name.or_else(|| prev.get(grad_idx).cloned()).unwrap_or_else(|| grad_idx.to_string())
+        }).collect();
+        self
+    }
+}
+
+/// Scale `vec` down to length `max_norm`, if it's longer.
+pub fn clip_l2(vec: Vec<f64>, max_norm: f64) -> Vec<f64> {
+    let norm = vec.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if norm > max_norm {
+        vec.into_iter().map(|v| v * max_norm / norm).collect()
+    } else {
+        vec
+    }
+}
+
+/// Area of each non-empty region of the scene described by `input_specs`, keyed by exclusive region key (e.g. `"01-"`), as in [`Step::region_areas`]. No targets are needed, and no coordinates are differentiated.
+pub fn compute_areas(input_specs: Vec<InputSpec>) -> Result<BTreeMap<String, f64>> {
+    if input_specs.is_empty() {
+        return Err(anyhow!("No shapes"));
+    }
+    let shapes: Vec<Shape<f64>> = input_specs.into_iter().map(|(shape, _)| shape).collect();
+    Ok(Scene::new(shapes).area_matrix())
+}
+
+/// Largest gap between any pair of the given shapes, or `None` if every pair overlaps. Each pair's gap is the mean of [`Gap::gap`] measured in each shape's frame (skipping directions where it's undefined).
+pub fn region_gap(shapes: &[Shape<D>], set_idxs: &[usize]) -> Option<Dual> {
+    set_idxs.iter().tuple_combinations().filter_map(|(&i, &j)| {
+        let gaps: Vec<Dual> = [ shapes[i].gap(&shapes[j]), shapes[j].gap(&shapes[i]) ].into_iter().flatten().collect();
+        let n = gaps.len() as f64;
+        if n == 0. { None } else { Some(gaps.into_iter().sum::<Dual>() / n) }
+    }).max_by(|a, b| a.v().total_cmp(&b.v()))
+}
+
+/// Largest overlap depth (see [`Overlap::overlap`]) between any pair of the given shapes, or `None` if no pair overlaps. Like [`region_gap`], each pair's depth is the mean of the depths measured in each shape's frame.
+pub fn region_overlap(shapes: &[Shape<D>], set_idxs: &[usize]) -> Option<Dual> {
+    set_idxs.iter().tuple_combinations().filter_map(|(&i, &j)| {
+        let depths: Vec<Dual> = [ shapes[i].overlap(&shapes[j]), shapes[j].overlap(&shapes[i]) ].into_iter().flatten().collect();
+        let n = depths.len() as f64;
+        if n == 0. { None } else { Some(depths.into_iter().sum::<Dual>() / n) }
+    }).max_by(|a, b| a.v().total_cmp(&b.v()))
+}
+
+/// For each region with nonzero area whose members shouldn't intersect at all (both its exclusive and inclusive targets are 0, e.g. `"01-"` and `"01*"`), the [`region_overlap`] of its members.
+pub fn unwanted_overlaps(shapes: &[Shape<D>], targets: &Targets<f64>, errors: &Errors) -> BTreeMap<String, Dual> {
+    targets.disjoints().into_iter().filter(|(key, target)| {
+        *target == 0.
+        && errors[key].actual_area.filter(|a| !a.is_zero() && *a > 0.).is_some()
+        && targets.get(&key.replace('-', "*")).is_none_or(|t| *t == 0.)
+    }).filter_map(|(key, _)| {
+        let set_idxs: Vec<usize> = key.chars().enumerate().filter(|(_, c)| *c != '-').map(|(idx, _)| idx).collect();
+        region_overlap(shapes, &set_idxs).map(|depth| (key, depth))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{duals::{D, Z}, shape::{circle, xyrr}, to::To, targets::TargetsMap, coord_getter::CoordGetters};
+
+    use super::*;
+    use test_log::test;
+
+    #[test]
+    fn error_breakdown() {
+        let inputs = vec![
+            (circle(0., 0., 1.), vec![ Z, Z, Z, ]),
+            (circle(1., 0., 1.), vec![ D, D, D, ]),
+            (circle(0., 1., 1.), vec![ D, D, D, ]),
+        ];
+        // Targets matching the actual areas, except for one region
+        let mut targets: TargetsMap<f64> = compute_areas(inputs.clone()).unwrap();
+        *targets.get_mut("0-2").unwrap() *= 4.;
+        let step = Step::new(inputs, targets.into());
+        let breakdown = step.error_breakdown();
+        assert_eq!(breakdown.len(), 7);
+        let worst = &breakdown[0];
+        assert_eq!(worst.key, "0-2");
+        assert_relative_eq!(worst.rel_error.unwrap(), -0.75, epsilon = 1e-12);
+        assert!(worst.pct_of_total > 40., "{:?}", breakdown);
+        for w in breakdown.windows(2) {
+            assert!(w[0].abs_error >= w[1].abs_error);
+        }
+        assert_relative_eq!(breakdown.iter().map(|e| e.pct_of_total).sum::<f64>(), 100., epsilon = 1e-9);
+        assert_relative_eq!(breakdown.iter().map(|e| e.abs_error).sum::<f64>(), step.error.v(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn clipped_steps() {
+        // Small circle barely overlapping a big one, with a large target intersection: steep, lopsided gradient
+        let inputs = vec![
+            (circle(0., 0., 3.), vec![ Z, Z, Z, ]),
+            (xyrr(3.2, 0., 0.5, 1.), vec![ D, D, D, D, ]),
+        ];
+        let targets: TargetsMap<f64> = [ ("0*", 9.), ("*1", 2.), ("01", 1.5) ].to();
+        let step = Step::new(inputs, targets.into());
+        let norm = |v: &Vec<f64>| v.iter().map(|v| v * v).sum::<f64>().sqrt();
+        let grad = step.error.d();
+        let lr = 100.;
+        assert!(lr * norm(&grad) > 1., "{:?}", grad);
+
+        let l2 = step.step_clipped_l2_vec(lr, 0.1);
+        assert_relative_eq!(norm(&l2), 0.1, epsilon = 1e-12);
+        // Direction is preserved
+        let scale = -l2[0] / grad[0];
+        for (s, g) in l2.iter().zip(grad.iter()) {
+            assert_relative_eq!(*s, -scale * g, epsilon = 1e-12);
+        }
+
+        let per_coord = step.step_clipped_vec(lr, 0.1, f64::INFINITY);
+        assert!(per_coord.iter().all(|s| s.abs() <= 0.1));
+        // Each large coordinate is clipped to the max independently, so the step is longer (and less aligned with the gradient) than the L2-clipped one
+        assert!(norm(&per_coord) > 0.15, "{:?}", per_coord);
+        let cos = |a: &Vec<f64>| -a.iter().zip(grad.iter()).map(|(a, g)| a * g).sum::<f64>() / norm(a) / norm(&grad);
+        assert!(cos(&per_coord) < cos(&l2) - 1e-3, "{} vs. {}", cos(&per_coord), cos(&l2));
+        // Overall cap applies after per-coordinate clipping
+        assert_relative_eq!(norm(&step.step_clipped_vec(lr, 0.1, 0.05)), 0.05, epsilon = 1e-12);
+
+        let nxt = step.step_clipped_l2(lr, 0.1);
+        assert!(nxt.error.v() < step.error.v());
+    }
+
+    #[test]
+    fn fit_metrics() {
+        let inputs = vec![
+            (circle(0., 0., 1.), vec![ Z, Z, Z, ]),
+            (circle(1., 0., 1.), vec![ D, D, D, ]),
+            (xyrr(0., 1., 1.5, 1.), vec![ D, D, D, D, ]),
+        ];
+        let placeholder: TargetsMap<f64> = [ ("0**", 1.), ("*1*", 1.), ("**2", 1.), ("01*", 0.5), ("0*2", 0.5), ("*12", 0.5), ("012", 0.2) ].to();
+        let actual = Step::new(inputs.clone(), placeholder.into());
+        // Perfect fit, with targets in different units than the actual areas
+        let targets: TargetsMap<f64> = compute_areas(inputs.clone()).unwrap().into_iter().map(|(key, area)| (key, 10. * area)).collect();
+        let metrics = Step::new(inputs.clone(), targets.into()).fit_metrics();
+        assert_relative_eq!(metrics.total_abs_error, 0., epsilon = 1e-12);
+        assert_relative_eq!(metrics.mean_rel_error, 0., epsilon = 1e-12);
+        assert_relative_eq!(metrics.max_rel_error, 0., epsilon = 1e-12);
+        assert_relative_eq!(metrics.weighted_jaccard, 1., epsilon = 1e-12);
+
+        let metrics = actual.fit_metrics();
+        assert!(metrics.total_abs_error > 0.1, "{:?}", metrics);
+        assert!(metrics.mean_rel_error > 0. && metrics.mean_rel_error <= metrics.max_rel_error, "{:?}", metrics);
+        assert!(metrics.weighted_jaccard > 0. && metrics.weighted_jaccard < 1., "{:?}", metrics);
+        // `Σ|a - t| = Σmax - Σmin`, and both fractions sum to 1
+        assert_relative_eq!(metrics.weighted_jaccard, (2. - metrics.total_abs_error) / (2. + metrics.total_abs_error), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn named_gradients() {
+        let targets: TargetsMap<f64> = [ ("0*", 3.), ("*1", 1.), ("01", 0.5) ].to();
+        let inputs = vec![
+            (circle(0., 0., 1.), vec![ Z, D, D, ]),
+            (xyrr(1., 0., 1., 2.), vec![ D, Z, D, D, ]),
+        ];
+        let step = Step::new(inputs.clone(), targets.into());
+        let named = step.named_gradients();
+        let names: Vec<&str> = named.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec![ "0.cy", "0.r", "1.cx", "1.rx", "1.ry" ]);
+        let coord_getters: CoordGetters<Step> = inputs.into();
+        assert_eq!(names, coord_getters.iter().map(|g| g.name.as_str()).collect::<Vec<_>>());
+        let grads: Vec<f64> = named.iter().map(|(_, grad)| *grad).collect();
+        assert_eq!(grads, step.error.d());
+    }
+
+    #[test]
+    fn region_areas() {
+        let targets: TargetsMap<f64> = [ ("0*", 3.), ("*1", 1.), ("01", 0.5) ].to();
+        let step = Step::new(
+            vec![
+                (circle(0., 0., 1.), vec![ Z, Z, Z, ]),
+                (circle(1., 0., 1.), vec![ D, Z, D, ]),
+            ],
+            targets.into(),
+        );
+        let areas = step.region_areas();
+        assert_eq!(areas.iter().map(|r| r.key.as_str()).collect::<Vec<_>>(), vec![ "-1", "0-", "01" ]);
+        let total: f64 = areas.iter().map(|r| r.area).sum();
+        assert_relative_eq!(total, step.total_area.v(), epsilon = 1e-12);
+        assert_eq!(areas[2].target_area, Some(0.5));
+        assert_eq!(areas[2].error, Some(step.errors["01"].error.v()));
+    }
+
+    #[test]
+    fn summary() {
+        let targets: TargetsMap<f64> = [ ("0*", 3.), ("*1", 1.), ("01", 0.5) ].to();
+        let step = Step::new(
+            vec![
+                (circle(0., 0., 1.), vec![ Z, Z, Z, ]),
+                (circle(1., 0., 1.), vec![ D, Z, D, ]),
+            ],
+            targets.into(),
+        );
+        let summary = step.summary();
+        assert_eq!(summary.error, step.error.v());
+        let areas = step.region_areas();
+        let intersection = areas.iter().find(|r| r.key == "01").unwrap().area;
+        // |A| + |B| - |A∩B| = |A∪B|
+        assert_relative_eq!(summary.per_set_area.iter().sum::<f64>() - intersection, summary.total_area, epsilon = 1e-12);
+        for (area, frac) in summary.per_set_area.iter().zip(summary.per_set_frac.iter()) {
+            assert_relative_eq!(area / summary.total_area, frac, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn canonicalize() {
+        let targets: TargetsMap<f64> = [ ("0*", 3.), ("*1", 1.), ("01", 0.5) ].to();
+        let inputs = |cx: f64| vec![
+            (circle(0., 0., 1.), vec![ Z, Z, Z, ]),
+            (circle(cx, 0., 1.), vec![ D, Z, D, ]),
+        ];
+        // Differ only in the last bit of one coordinate
+        let mut a = Step::new(inputs(0.1 + 0.2), targets.clone().into());
+        let mut b = Step::new(inputs(0.3), targets.into());
+        assert_ne!(a.shapes, b.shapes);
+        a.canonicalize(12);
+        b.canonicalize(12);
+        assert_eq!(a.shapes, b.shapes);
+        assert_eq!(a.error, b.error);
+    }
+
+    #[test]
+    fn compute_areas_match_step() {
+        let inputs = vec![
+            (circle(0., 0., 1.), vec![ Z, Z, Z, ]),
+            (xyrr(1., 0., 1., 2.), vec![ D, D, D, D, ]),
+            (circle(0., 1., 1.5), vec![ D, D, Z, ]),
+        ];
+        let areas = compute_areas(inputs.clone()).unwrap();
+        assert_eq!(areas.len(), 7);
+        // Any targets give the same areas
+        for targets in [
+            [ ("0**", 1.), ("*1*", 1.), ("**2", 1.), ("01*", 0.5), ("0*2", 0.5), ("*12", 0.5), ("012", 0.2) ],
+            [ ("0**", 5.), ("*1*", 0.1), ("**2", 2.), ("01*", 0.), ("0*2", 1.), ("*12", 0.), ("012", 0.) ],
+        ] {
+            let targets: TargetsMap<f64> = targets.to();
+            let step = Step::new(inputs.clone(), targets.into());
+            let expected: BTreeMap<String, f64> = step.region_areas().into_iter().map(|r| (r.key, r.area)).collect();
+            assert_eq!(areas, expected);
+        }
+        assert!(compute_areas(vec![]).is_err());
+    }
+
+    #[test]
+    fn error_only() {
+        let targets: TargetsMap<f64> = [
+            ("0**", 3.), ("*1*", 5.), ("**2", 7.),
+            ("01*", 1.), ("0*2", 1.), ("*12", 1.),
+            ("012", 0.3),
+        ].to();
+        let targets: Targets<f64> = Targets::from(targets).with_weights([ ("01-", 2.) ].to());
+        let inputs = vec![
+            (circle(0., 0., 1.), vec![ Z, Z, Z, ]),
+            (xyrr(1., 0., 1., 2.), vec![ D, D, D, D, ]),
+            (circle(0., 1., 1.5), vec![ D, D, Z, ]),
+        ];
+        let step = Step::new(inputs.clone(), targets.clone());
+        assert_eq!(Step::error_only(inputs.clone(), targets.clone()).unwrap(), step.error.v());
+        assert!(Step::error_only(inputs[..2].to_vec(), targets).is_err());
+    }
+
+    #[test]
+    fn clamp_negative_areas() {
+        let targets: TargetsMap<f64> = [ ("0*", 3.), ("*1", 1.), ("01", 0.5) ].to();
+        let targets: Targets<f64> = targets.into();
+        let key = "01".to_string();
+        // The noise threshold is relative to the scene's total area, so the same (relative) region areas are treated the same at any scale
+        for scale in [ 1e-2, 1., 1e3 ] {
+            let inputs = vec![
+                (circle(0., 0., scale), vec![ Z, Z, Z, ]),
+                (circle(3. * scale, 0., scale), vec![ D, Z, D, ]),
+            ];
+            // Disjoint circles; give them a tiny intersection region with a slightly-negative area, simulating floating-point noise
+            let scene_with_01_area = |area: f64| {
+                let mut scene = Scene::new(Shapes::from_vec(&inputs));
+                let mut region = scene.components[0].regions[0].clone();
+                region.key = "01".to_string();
+                region.total_area = Dual::new(area, vec![0.; 2]);
+                scene.components[0].regions.push(region);
+                scene
+            };
+            let area = scale * scale;
+            let scene = scene_with_01_area(-1e-6 * area);
+            assert_eq!(scene.area(&key).unwrap().v(), -1e-6 * area);
+            assert_eq!(scene.area_clamped(&key).unwrap().v(), 0.);
+            assert_eq!(scene.negative_region_report(), vec![]);
+            let total_area = scene.area(&"**".to_string()).unwrap();
+            let actual_frac = |loss: &LossConfig| Step::compute_errors(&scene, &targets, &total_area, loss)[&key].actual_frac;
+            assert!(actual_frac(&LossConfig::default()) < 0.);
+            assert_eq!(actual_frac(&LossConfig { clamp_negative_areas: true, ..LossConfig::default() }), 0.);
+
+            // Too negative to be noise: not clamped, and reported
+            let scene = scene_with_01_area(-0.1 * area);
+            assert_eq!(scene.area_clamped(&key).unwrap().v(), -0.1 * area);
+            assert_eq!(scene.negative_region_report(), vec![ ("01".to_string(), -0.1 * area) ]);
+        }
     }
 }
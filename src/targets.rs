@@ -1,6 +1,7 @@
 use core::panic;
 use std::{collections::{BTreeMap, BTreeSet}, ops::{Sub, Add, Deref}, fmt::Display};
 
+use anyhow::{anyhow, Result};
 use num_traits::pow;
 use serde::{Serialize, Deserialize};
 use tsify::{declare, Tsify};
@@ -16,6 +17,76 @@ pub struct Targets<D> {
     pub given: BTreeSet<String>,
     pub n: usize,
     pub total_area: D,
+    /// Error weights for disjoint region keys (wildcard keys' weights are spread over their regions, see [`Targets::with_weights`]); regions not present here have weight 1 (see [`Targets::disjoint_weights`]).
+    #[serde(default)]
+    pub weights: BTreeMap<String, f64>,
+    /// Names of the sets, by index (e.g. `["KRAS", "TP53"]`); empty if the sets are unnamed (see [`Targets::with_labels`]).
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+/// A target region's area, optionally with a weight for its error (e.g. `3` or `{ "area": 3, "weight": 2 }`).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+#[serde(untagged)]
+pub enum TargetSpec {
+    Area(f64),
+    Weighted { area: f64, weight: f64 },
+}
+
+#[declare]
+pub type TargetSpecs = BTreeMap<String, TargetSpec>;
+
+/// Relative (to the total target area) tolerance used by [`Targets::validate`].
+pub const VALIDATE_REL_TOL: f64 = 1e-9;
+
+/// A violated feasibility constraint among (expanded) target areas, found by [`Targets::validate`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+#[serde(tag = "kind")]
+pub enum TargetWarning {
+    /// A region (or union of regions) has negative area.
+    NegativeArea { key: String, area: f64 },
+    /// A region's area exceeds that of a region containing it (e.g. `"01"` > `"0*"`).
+    ExceedsSuperset { key: String, area: f64, superset: String, superset_area: f64 },
+    /// A region's area isn't the sum of its two halves (e.g. `"0*"` ≠ `"0-"` + `"01"`), meaning the given targets over-determine it inconsistently.
+    InconsistentSum { key: String, area: f64, parts: (String, String), sum: f64 },
+}
+
+/// Summary of a [`Targets`]' implied union area and per-region fractions (see [`Targets::summary`]).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetsSummary {
+    pub total_area: f64,
+    pub implied_total: f64,
+    pub per_region_fractions: BTreeMap<String, f64>,
+}
+
+impl From<TargetSpecs> for Targets<f64> {
+    fn from(specs: TargetSpecs) -> Self {
+        let mut areas: TargetsMap<f64> = BTreeMap::new();
+        let mut weights: BTreeMap<String, f64> = BTreeMap::new();
+        for (key, spec) in specs {
+            match spec {
+                TargetSpec::Area(area) => {
+                    areas.insert(key, area);
+                }
+                TargetSpec::Weighted { area, weight } => {
+                    areas.insert(key.clone(), area);
+                    weights.insert(key, weight);
+                }
+            }
+        }
+        Targets::new(areas).with_weights(weights)
+    }
+}
+
+impl Targets<f64> {
+    /// [`Targets::from`] [`TargetSpecs`] whose keys may be written with `labels` (see [`Targets::with_labels`]). Keys (including weighted ones) are translated to positional keys first, so unrecognized labels are errors.
+    pub fn from_labeled_specs(labels: Vec<String>, specs: TargetSpecs) -> Result<Targets<f64>> {
+        let specs = translate_keys(&labels, specs)?;
+        let mut targets: Targets<f64> = specs.into();
+        targets.labels = labels;
+        Ok(targets)
+    }
 }
 
 type Neighbor = (char, String);
@@ -158,7 +229,43 @@ impl<D: Arg> Targets<D>
             given: given.keys().cloned().collect(),
             n,
             total_area,
+            weights: BTreeMap::new(),
+            labels: vec![],
+        }
+    }
+    /// Build [`Targets`] for sets named by `labels`. Keys may be positional (e.g. `"01"`, `"0-2"`), or comma-separated lists of labels (e.g. `"KRAS,TP53"`), meaning the intersection of those sets (whether or not it's also in the others, i.e. `'*'` at all other positions, as in venn.js); see [`translate_key`].
+    pub fn with_labels(labels: Vec<String>, given: TargetsMap<D>) -> Result<Targets<D>> {
+        let given = translate_keys(&labels, given)?;
+        let mut targets = Targets::new(given);
+        targets.labels = labels;
+        Ok(targets)
+    }
+    /// Set error weights for region keys. A disjoint key (e.g. `"01-"`) weights that region's error; a wildcard key (e.g. `"0*"`) weights each disjoint region it contains (e.g. `"0-"` and `"01"`). A region matched by several keys gets the product of their weights.
+    pub fn with_weights(mut self, weights: BTreeMap<String, f64>) -> Self {
+        for (key, weight) in &weights {
+            if key.len() != self.n {
+                panic!("Weight key {} has length {}, expected {}", key, key.len(), self.n);
+            }
+            for region in self.disjoints().into_keys() {
+                let contained = key.chars().zip(region.chars()).all(|(k, r)| k == '*' || k == r);
+                if contained {
+                    *self.weights.entry(region).or_insert(1.) *= weight;
+                }
+            }
+        }
+        self
+    }
+    /// Error weight for each disjoint region, normalized to average 1 (so that weighted and unweighted total errors are comparable), or `None` if no weights were given.
+    pub fn disjoint_weights(&self) -> Option<BTreeMap<String, f64>> {
+        if self.weights.is_empty() {
+            return None;
         }
+        let weights: BTreeMap<String, f64> = self.disjoints().into_keys().map(|key| {
+            let weight = self.weights.get(&key).cloned().unwrap_or(1.);
+            (key, weight)
+        }).collect();
+        let mean = weights.values().sum::<f64>() / weights.len() as f64;
+        Some(weights.into_iter().map(|(key, weight)| (key, weight / mean)).collect())
     }
     pub fn disjoints(&self) -> TargetsMap<D> {
         let mut map: TargetsMap<D> = BTreeMap::new();
@@ -177,6 +284,86 @@ impl<D: Arg> Targets<D>
         }
     }
 }
+impl Targets<f64> {
+    /// Union area of all sets, via inclusion-exclusion over (inclusive) intersection targets: `Σ|Aᵢ| - Σ|Aᵢ∩Aⱼ| + …`. Should equal [`Self::total_area`]; a mismatch indicates inconsistent targets.
+    pub fn implied_total(&self) -> f64 {
+        (1..(1usize << self.n)).map(|mask| {
+            let key: String = (0..self.n).map(|idx| if mask & (1 << idx) != 0 { Self::idx(idx) } else { '*' }).collect();
+            let sign = if mask.count_ones() % 2 == 1 { 1. } else { -1. };
+            sign * self.all[&key]
+        }).sum()
+    }
+    /// Each disjoint region's target, as a fraction of [`Self::total_area`].
+    pub fn per_region_fractions(&self) -> BTreeMap<String, f64> {
+        self.disjoints().into_iter().map(|(key, area)| (key, area / self.total_area)).collect()
+    }
+    /// [`Self::total_area`], [`Self::implied_total`], and [`Self::per_region_fractions`] together.
+    pub fn summary(&self) -> TargetsSummary {
+        TargetsSummary {
+            total_area: self.total_area,
+            implied_total: self.implied_total(),
+            per_region_fractions: self.per_region_fractions(),
+        }
+    }
+    /// Check (expanded) target areas for basic feasibility: non-negativity, subsets not exceeding supersets, and consistent sums. Returns warnings (empty if none were found), since slightly-infeasible targets can still be approximated.
+    pub fn validate(&self) -> Vec<TargetWarning> {
+        let tol = VALIDATE_REL_TOL * self.total_area.abs();
+        let mut warnings = Vec::new();
+        for (key, &area) in &self.all {
+            if area < -tol {
+                warnings.push(TargetWarning::NegativeArea { key: key.clone(), area });
+            }
+            for (idx, ch) in key.char_indices() {
+                let with = |ch: char| format!("{}{}{}", &key[..idx], ch, &key[idx + 1..]);
+                if ch == '*' {
+                    let parts = (with('-'), with(Self::idx(idx)));
+                    let sum = self.all[&parts.0] + self.all[&parts.1];
+                    if (area - sum).abs() > tol {
+                        warnings.push(TargetWarning::InconsistentSum { key: key.clone(), area, parts, sum });
+                    }
+                } else {
+                    let superset = with('*');
+                    let superset_area = self.all[&superset];
+                    if area > superset_area + tol {
+                        warnings.push(TargetWarning::ExceedsSuperset { key: key.clone(), area, superset, superset_area });
+                    }
+                }
+            }
+        }
+        warnings
+    }
+}
+
+/// Translate a target key to its positional form: positional keys (of length `labels.len()`, with each character either `'-'`, `'*'`, or its set's index) are returned as-is, while comma-separated lists of labels become keys with those sets' indices at their positions, and `'*'` elsewhere (e.g. `"TP53"` → `"*1"`, given labels `["KRAS", "TP53"]`).
+pub fn translate_key(labels: &[String], key: &str) -> Result<String> {
+    let n = labels.len();
+    let positional = key.chars().count() == n && key.chars().enumerate().all(|(idx, ch)| ch == '-' || ch == '*' || ch == Targets::<f64>::idx(idx));
+    if positional {
+        return Ok(key.to_string());
+    }
+    let mut chars = vec!['*'; n];
+    for label in key.split(',').map(|label| label.trim()) {
+        let idx = labels.iter().position(|l| l == label).ok_or_else(|| anyhow!("Unrecognized label {:?} in key {:?}; expected one of {:?}", label, key, labels))?;
+        if chars[idx] != '*' {
+            return Err(anyhow!("Duplicate label {:?} in key {:?}", label, key));
+        }
+        chars[idx] = Targets::<f64>::idx(idx);
+    }
+    Ok(chars.into_iter().collect())
+}
+
+/// Apply [`translate_key`] to each key of `map`, erroring if two keys refer to the same region.
+pub fn translate_keys<V>(labels: &[String], map: BTreeMap<String, V>) -> Result<BTreeMap<String, V>> {
+    let mut translated: BTreeMap<String, V> = BTreeMap::new();
+    for (key, value) in map {
+        let positional = translate_key(labels, &key)?;
+        if translated.insert(positional.clone(), value).is_some() {
+            return Err(anyhow!("Key {:?} refers to region {}, which was already given", key, positional));
+        }
+    }
+    Ok(translated)
+}
+
 impl<D> Targets<D> {
     pub fn none_key(&self) -> String {
         String::from_utf8(vec![b'-'; self.n]).unwrap()
@@ -212,6 +399,64 @@ mod tests {
         assert_eq!(items, expected.into_iter().map(|(k, v)| (k.to_string(), v)).collect::<Vec<_>>());
     }
 
+    #[test]
+    fn weights() {
+        let specs: TargetSpecs = [
+            ("0*", TargetSpec::Area(9.)),
+            ("*1", TargetSpec::Area(3.)),
+            ("01", TargetSpec::Weighted { area: 1., weight: 4. }),
+        ].to();
+        let targets: Targets<f64> = specs.into();
+        assert_eq!(targets.get("0-"), Some(&8.));
+        assert_eq!(targets.weights, BTreeMap::from([ ("01".to_string(), 4.) ]));
+        // Disjoint regions "-1", "0-", "01" have weights 1, 1, 4; normalized to mean 1
+        let weights = targets.disjoint_weights().unwrap();
+        assert_eq!(weights, BTreeMap::from([ ("-1".to_string(), 0.5), ("0-".to_string(), 0.5), ("01".to_string(), 2.) ]));
+
+        let unweighted: Targets<f64> = Targets::new([ ("0*", 9.), ("*1", 3.), ("01", 1.) ].to());
+        assert_eq!(unweighted.disjoint_weights(), None);
+
+        // Wildcard keys' weights apply to each region they contain; overlapping weights multiply
+        let specs: TargetSpecs = [
+            ("0*", TargetSpec::Weighted { area: 9., weight: 2. }),
+            ("*1", TargetSpec::Area(3.)),
+            ("01", TargetSpec::Weighted { area: 1., weight: 3. }),
+        ].to();
+        let targets: Targets<f64> = specs.into();
+        assert_eq!(targets.weights, BTreeMap::from([ ("0-".to_string(), 2.), ("01".to_string(), 6.) ]));
+    }
+
+    #[test]
+    fn implied_total() {
+        let targets = Targets::new([ ("0*", 9.), ("*1", 3.), ("01", 1.) ].to());
+        // |A∪B| = |A| + |B| - |A∩B|
+        assert_eq!(targets.implied_total(), 9. + 3. - 1.);
+        assert_eq!(targets.implied_total(), targets.total_area);
+        let fractions = targets.per_region_fractions();
+        assert_eq!(fractions, BTreeMap::from([ ("-1".to_string(), 2. / 11.), ("0-".to_string(), 8. / 11.), ("01".to_string(), 1. / 11.) ]));
+    }
+
+    #[test]
+    fn validate_feasible() {
+        let targets = Targets::new([ ("0*", 9.), ("*1", 3.), ("01", 1.) ].to());
+        assert_eq!(targets.validate(), vec![]);
+    }
+
+    #[test]
+    fn validate_infeasible() {
+        // Intersection larger than either set
+        let targets = Targets::new([ ("0*", 1.), ("*1", 1.), ("01", 2.) ].to());
+        let warnings = targets.validate();
+        assert!(warnings.contains(&TargetWarning::NegativeArea { key: "0-".to_string(), area: -1. }), "{:?}", warnings);
+        assert!(warnings.contains(&TargetWarning::ExceedsSuperset { key: "01".to_string(), area: 2., superset: "0*".to_string(), superset_area: 1. }), "{:?}", warnings);
+        assert!(warnings.contains(&TargetWarning::ExceedsSuperset { key: "01".to_string(), area: 2., superset: "*1".to_string(), superset_area: 1. }), "{:?}", warnings);
+
+        // Over-determined: "0*" ≠ "0-" + "01"
+        let targets = Targets::new([ ("0*", 9.), ("0-", 5.), ("01", 1.), ("*1", 3.) ].to());
+        let warnings = targets.validate();
+        assert!(warnings.iter().any(|w| matches!(w, TargetWarning::InconsistentSum { .. })), "{:?}", warnings);
+    }
+
     #[test]
     fn expand2() {
         test(vec![
@@ -273,4 +518,31 @@ mod tests {
             ("012",  1),
         ]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn labeled_keys() {
+        let labels: Vec<String> = [ "KRAS", "TP53", "EGFR" ].map(String::from).to_vec();
+        let key = |key: &str| translate_key(&labels, key);
+        assert_eq!(key("0-2").unwrap(), "0-2");
+        assert_eq!(key("**2").unwrap(), "**2");
+        assert_eq!(key("KRAS").unwrap(), "0**");
+        assert_eq!(key("KRAS,TP53").unwrap(), "01*");
+        assert_eq!(key("EGFR, KRAS").unwrap(), "0*2");
+        assert!(key("BRAF").is_err());
+        assert!(key("KRAS,KRAS").is_err());
+        let given: TargetsMap<i64> = [ ("KRAS", 3), ("TP53", 5), ("EGFR", 2), ("KRAS,TP53", 1), ("01*", 1) ].into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+        assert!(Targets::with_labels(labels, given).is_err());
+
+        // Weighted label keys are translated before their weights are spread over regions
+        let labels: Vec<String> = [ "A", "B" ].map(String::from).to_vec();
+        let specs: TargetSpecs = [
+            ("A", TargetSpec::Weighted { area: 3., weight: 2. }),
+            ("B", TargetSpec::Area(5.)),
+            ("A,B", TargetSpec::Area(1.)),
+        ].to();
+        let targets = Targets::from_labeled_specs(labels.clone(), specs).unwrap();
+        assert_eq!(targets.weights, BTreeMap::from([ ("0-".to_string(), 2.), ("01".to_string(), 2.) ]));
+        let specs: TargetSpecs = [ ("C", TargetSpec::Weighted { area: 3., weight: 2. }) ].to();
+        assert!(Targets::from_labeled_specs(labels, specs).is_err());
+    }
+}
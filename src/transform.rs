@@ -10,6 +10,8 @@ pub enum Transform<D> {
     Scale(D),
     ScaleXY(R2<D>),
     Rotate(D),
+    /// Reflect across the line through the origin with the given direction
+    Reflect(R2<D>),
 }
 
 impl<
@@ -29,6 +31,7 @@ where
             Transform::Scale(v) => Transform::Scale(v.recip()),
             Transform::ScaleXY(v) => Transform::ScaleXY(R2 { x: v.clone().x.recip(), y: v.clone().y.recip() }),
             Transform::Rotate(v) => Transform::Rotate(-v),
+            Transform::Reflect(v) => Transform::Reflect(v),
         }
     }
 }